@@ -0,0 +1,44 @@
+//! Parsing many small objects with `Value::from_str` vs. a reused `Arena`.
+//! Run with `cargo bench`.
+use smoljson::arena::Arena;
+use smoljson::Value;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+fn time(label: &str, mut f: impl FnMut()) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..30 {
+        let t = Instant::now();
+        f();
+        best = best.min(t.elapsed());
+    }
+    println!("  {:<24} {:>10.3?}", label, best);
+    best
+}
+
+fn main() {
+    let docs = (0..50_000)
+        .map(|i| {
+            format!(
+                r#"{{"id": {}, "user": "u{}", "ok": true, "pt": {{"x": 1.5, "y": -2}}, "tags": ["a", "b\n"]}}"#,
+                i, i
+            )
+        })
+        .collect::<Vec<_>>();
+    println!("{} small objects", docs.len());
+    let value = time("Value::from_str", || {
+        for d in &docs {
+            black_box(Value::from_str(d).unwrap());
+        }
+    });
+    let mut arena = Arena::new();
+    let arena = time("Arena::parse (reused)", || {
+        for d in &docs {
+            black_box(arena.parse(d).unwrap().root().len());
+        }
+    });
+    println!(
+        "  speedup: {:.2}x",
+        value.as_secs_f64() / arena.as_secs_f64()
+    );
+}
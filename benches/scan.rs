@@ -0,0 +1,121 @@
+//! Compares the word-at-a-time scanning loops in `smoljson::scan` against the
+//! byte-at-a-time loops they replaced, on large minified and pretty-printed
+//! documents. Run with `cargo bench --features bench`.
+use smoljson::{scan, Dialect, Value};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+fn bytewise_skip_ws(bs: &[u8], mut p: usize) -> usize {
+    while p < bs.len() && matches!(bs[p], b'\n' | b' ' | b'\t' | b'\r') {
+        p += 1;
+    }
+    p
+}
+
+fn bytewise_find_quote_or_escape(bs: &[u8], mut p: usize) -> usize {
+    while p < bs.len() && bs[p] != b'"' && bs[p] != b'\\' {
+        p += 1;
+    }
+    p
+}
+
+fn bytewise_find_block_end(bs: &[u8], mut p: usize) -> usize {
+    while p + 1 < bs.len() && !(bs[p] == b'*' && bs[p + 1] == b'/') {
+        p += 1;
+    }
+    p
+}
+
+// A crude tokenizer which only exercises the loops being compared, so that
+// the difference isn't drowned out by everything else the parser does.
+fn walk(
+    bs: &[u8],
+    ws: fn(&[u8], usize) -> usize,
+    q: fn(&[u8], usize) -> usize,
+    c: fn(&[u8], usize) -> usize,
+) -> usize {
+    let (mut p, mut n) = (0, 0);
+    while p < bs.len() {
+        p = ws(bs, p);
+        match bs.get(p) {
+            Some(b'"') => {
+                p = q(bs, p + 1) + 1;
+                n += 1;
+            }
+            Some(b'/') if bs.get(p + 1) == Some(&b'*') => p = c(bs, p + 2) + 2,
+            Some(_) => p += 1,
+            None => {}
+        }
+    }
+    n
+}
+
+fn make_doc(pretty: bool) -> String {
+    let recs = (0..20_000)
+        .map(|i| {
+            smoljson::json!({
+                "id": i,
+                "name": (format!("record number {} with a reasonably long name", i)),
+                "tags": ["alpha", "beta", "gamma"],
+                "nested": {"flag": (i % 2 == 0), "score": (i as f64 * 0.5), "note": "escaped \"quote\""},
+            })
+        })
+        .collect::<Value>();
+    let mut s = recs.to_string(pretty);
+    if pretty {
+        // Sprinkle in some block comments, as CJSON config files tend to have.
+        s = s.replace(
+            "\"tags\"",
+            "/* a block comment explaining the tags field */ \"tags\"",
+        );
+    }
+    s
+}
+
+fn time<R>(label: &str, bytes: usize, mut f: impl FnMut() -> R) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..30 {
+        let t = Instant::now();
+        black_box(f());
+        best = best.min(t.elapsed());
+    }
+    let mbps = bytes as f64 / best.as_secs_f64() / (1024.0 * 1024.0);
+    println!("  {:<28} {:>10.3?} {:>9.1} MiB/s", label, best, mbps);
+    best
+}
+
+fn main() {
+    for &pretty in &[false, true] {
+        let doc = make_doc(pretty);
+        let bs = doc.as_bytes();
+        println!(
+            "{} ({} bytes)",
+            if pretty {
+                "pretty + comments"
+            } else {
+                "minified"
+            },
+            bs.len()
+        );
+        let old = time("scan: bytewise", bs.len(), || {
+            walk(
+                bs,
+                bytewise_skip_ws,
+                bytewise_find_quote_or_escape,
+                bytewise_find_block_end,
+            )
+        });
+        let new = time("scan: swar", bs.len(), || {
+            walk(
+                bs,
+                scan::skip_ws,
+                scan::find_quote_or_escape,
+                scan::find_block_end,
+            )
+        });
+        println!("  speedup: {:.2}x", old.as_secs_f64() / new.as_secs_f64());
+        time("full parse (Value)", bs.len(), || {
+            Value::from_str_with(&doc, Dialect::LOOSE).unwrap()
+        });
+    }
+}
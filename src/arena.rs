@@ -0,0 +1,88 @@
+//! Parsing into a caller-supplied, reusable arena.
+//!
+//! An [`Arena`] owns the buffers of a [`Document`] (and the scratch memory
+//! used while parsing one), and every document parsed with [`Arena::parse`]
+//! is built in them. Once that document is dropped, the buffers go back to the
+//! arena, so the next parse reuses their memory. Once they've grown to fit the
+//! documents being parsed, parsing doesn't allocate. This is intended for
+//! request-scoped parsing, e.g. one arena per worker thread.
+//!
+//! Note that this isn't a bump allocator which [`Value`](crate::Value)s can be
+//! allocated in: documents are always [`Document`]s, and only one can be alive
+//! per arena at a time.
+//!
+//! ```
+//! # use smoljson::{json, arena::Arena};
+//! let mut arena = Arena::new();
+//! for (i, req) in [r#"{"id": 1, "tags": ["x"]}"#, r#"{"id": 2, "tags": []}"#].iter().enumerate() {
+//!     let doc = arena.parse(req).unwrap();
+//!     let v = doc.root();
+//!     assert_eq!(v.get("id").and_then(|id| id.as_u64()), Some(i as u64 + 1));
+//!     assert!(v.get("tags").unwrap().is_array());
+//! }
+//! ```
+use crate::read::{Dialect, Result};
+use crate::tape::{Document, Scratch};
+
+#[derive(Debug)]
+pub struct Arena {
+    // Never parsed into, only used to hold on to the buffers between parses.
+    spare: Document<'static>,
+    scratch: Scratch,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arena {
+    pub const fn new() -> Self {
+        Self {
+            spare: Document::empty(),
+            scratch: Scratch::new(),
+        }
+    }
+
+    /// Parse `input` using the [default `Dialect`](Dialect::DEFAULT).
+    pub fn parse<'x>(&'x mut self, input: &'x str) -> Result<ArenaDocument<'x>> {
+        self.parse_with(input, Dialect::DEFAULT)
+    }
+
+    /// Parse `input` using the given `Dialect`.
+    pub fn parse_with<'x>(&'x mut self, input: &'x str, d: Dialect) -> Result<ArenaDocument<'x>> {
+        let mut doc = self.spare.with_src(input);
+        let res = doc.read(d, &mut self.scratch);
+        // Even on failure, this gives the buffers back when it's dropped.
+        let doc = ArenaDocument { arena: self, doc };
+        tri!(res);
+        Ok(doc)
+    }
+
+    /// Bytes currently reserved by the arena.
+    pub fn capacity(&self) -> usize {
+        self.spare.capacity() + self.scratch.capacity()
+    }
+}
+
+/// A [`Document`] parsed in an [`Arena`], which returns its buffers to the
+/// arena when dropped.
+#[derive(Debug)]
+pub struct ArenaDocument<'x> {
+    arena: &'x mut Arena,
+    doc: Document<'x>,
+}
+
+impl<'x> core::ops::Deref for ArenaDocument<'x> {
+    type Target = Document<'x>;
+    fn deref(&self) -> &Document<'x> {
+        &self.doc
+    }
+}
+
+impl Drop for ArenaDocument<'_> {
+    fn drop(&mut self) {
+        self.arena.spare = self.doc.with_src("");
+    }
+}
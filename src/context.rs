@@ -0,0 +1,148 @@
+//! Reusable parsing state, for when many documents are parsed one after
+//! another (for example, the lines of an NDJSON file).
+use crate::read::{Dialect, Reader, Result, Token};
+use crate::value::{Borrowed, Owned, Strs, Value};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use core::cell::{Cell, RefCell};
+
+/// Keeps the scratch buffer used for unescaping strings between documents,
+/// and can optionally intern object keys across documents.
+///
+/// ```
+/// # use smoljson::{json, ParseContext};
+/// let cx = ParseContext::new().intern_keys(1024);
+/// let lines = "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"name\": \"b\"}";
+/// let mut ids = vec![];
+/// for line in lines.lines() {
+///     let v = cx.parse_owned(line).unwrap();
+///     ids.push(v["id"].as_u64().unwrap());
+/// }
+/// assert_eq!(ids, [1, 2]);
+/// ```
+///
+/// ## Key interning
+///
+/// When enabled with [`ParseContext::intern_keys`], keys which would
+/// otherwise need to be allocated (always the case for
+/// [`ParseContext::parse_owned`], and for keys containing escapes otherwise)
+/// are looked up in a set of previously seen keys, and borrowed from there if
+/// present.
+///
+/// The interned keys are owned by the context, which is why the `Value`s it
+/// returns borrow from it (even from `parse_owned`), and they're freed when
+/// it's dropped. To keep memory use bounded, you give the total number of
+/// bytes of keys the context may intern, and past that point new keys are
+/// allocated normally. This works well when a small set of keys is repeated
+/// many times, which is the case it's intended for.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    buf: RefCell<String>,
+    dialect: Dialect,
+    keys: Interner,
+}
+
+#[derive(Debug, Default)]
+struct Interner {
+    // Only ever added to, never removed from or modified (nothing but `get`
+    // touches it).
+    set: RefCell<BTreeSet<String>>,
+    budget: Cell<usize>,
+}
+
+impl Interner {
+    #[allow(unsafe_code)]
+    fn get(&self, k: &str) -> Option<&str> {
+        let mut set = self.set.borrow_mut();
+        let s: *const str = match set.get(k) {
+            Some(s) => s.as_str(),
+            None if k.len() > self.budget.get() => return None,
+            None => {
+                self.budget.set(self.budget.get() - k.len());
+                set.insert(k.into());
+                set.get(k)?.as_str()
+            }
+        };
+        // SAFETY: `s` points into the heap buffer of a `String` in `set`, and
+        // we return it borrowed for as long as `self` is. During that time:
+        // - The `String` can't be removed, mutated, or dropped: `set` is only
+        //   ever inserted into, and `self` can't be dropped or mutably borrowed.
+        // - Inserting may move the `String` itself within the `BTreeSet`, but
+        //   moving a `String` doesn't move (or reallocate) its buffer.
+        // So the buffer stays alive and unchanged. `test_interned_keys_stable`
+        // in `tests/context.rs` checks this.
+        Some(unsafe { &*s })
+    }
+}
+
+impl ParseContext {
+    /// A context using the [default `Dialect`](Dialect::DEFAULT), with key
+    /// interning disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_dialect(dialect: Dialect) -> Self {
+        Self {
+            dialect,
+            ..Self::default()
+        }
+    }
+
+    /// Allow interning up to `max_bytes` worth of object keys. See the
+    /// [type-level documentation](ParseContext#key-interning) for details.
+    pub fn intern_keys(self, max_bytes: usize) -> Self {
+        self.keys.budget.set(max_bytes);
+        self
+    }
+
+    /// Parse a document, borrowing strings from `input` where possible.
+    pub fn parse<'a>(&'a self, input: &'a str) -> Result<Value<'a>> {
+        self.run(input, &mut Interning(&self.keys, Borrowed))
+    }
+
+    /// Parse a document, copying every string out of `input` so that the
+    /// result doesn't borrow from it.
+    pub fn parse_owned(&self, input: &str) -> Result<Value<'_>> {
+        self.run(input, &mut Interning(&self.keys, Owned))
+    }
+
+    fn run<'a, 'v, S: Strs<'a, 'v>>(&self, input: &'a str, s: &mut S) -> Result<Value<'v>> {
+        let mut de = Reader::with_buf(input, self.dialect, self.buf.take());
+        let res = match Value::read_with(&mut de, s) {
+            Ok(v) => match de.skipnpeek() {
+                Ok(None) => Ok(v),
+                Ok(Some(_)) => Err(de.err()),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        self.buf.replace(de.into_buf());
+        res
+    }
+}
+
+struct Interning<'i, S>(&'i Interner, S);
+
+impl<'a, 'v, 'i: 'v, S: Strs<'a, 'v>> Strs<'a, 'v> for Interning<'i, S> {
+    fn string(&mut self, de: &mut Reader<'a>, t: Token<'a>) -> Cow<'v, str> {
+        self.1.string(de, t)
+    }
+    fn key(&mut self, de: &mut Reader<'a>, t: Token<'a>) -> Cow<'v, str> {
+        let interned = match &t {
+            Token::StrBorrow(_) if !S::COPIES => None,
+            Token::StrBorrow(k) => self.0.get(k),
+            Token::StrOwn(k) => self.0.get(k),
+            _ => None,
+        };
+        match (interned, t) {
+            (Some(k), Token::StrOwn(s)) => {
+                de.reclaim(s);
+                Cow::Borrowed(k)
+            }
+            (Some(k), _) => Cow::Borrowed(k),
+            (None, t) => self.1.key(de, t),
+        }
+    }
+}
@@ -0,0 +1,504 @@
+//! A lossless concrete syntax tree.
+//!
+//! A [`Cst`] keeps every token of the source, including whitespace and
+//! comments, so printing it gives back the input byte-for-byte. This is meant
+//! for tools which work with files owned by people, like `.jsonc` settings,
+//! where comments and formatting matter.
+//!
+//! Parsing never fails: problems are collected in [`Cst::errors`], and the
+//! tree contains whatever could be made sense of.
+//!
+//! ```
+//! # use smoljson::{Dialect, cst::Cst};
+//! let src = "{\n    // Use tabs?\n    \"tabs\": true, // default: false\n}";
+//! let cst = Cst::parse(src, Dialect::CJSON);
+//! assert_eq!(cst.to_string(), src);
+//! let tabs = cst.root().unwrap().property("tabs").unwrap();
+//! assert_eq!(tabs.leading_comments(), ["// Use tabs?"]);
+//! assert_eq!(tabs.trailing_comment(), Some("// default: false"));
+//! assert_eq!(tabs.value().unwrap().text(), "true");
+//! ```
+use crate::lex::{self, Kind, Lexer, Tok};
+use crate::location::Segment;
+use crate::pointer;
+use crate::read::Dialect;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+    /// An object entry: a key, and (unless the document is broken) a colon and
+    /// a value.
+    Property,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub span: Range<usize>,
+    pub message: &'static str,
+}
+
+#[derive(Debug, Clone)]
+struct NodeData {
+    kind: NodeKind,
+    // Index range into `Cst::toks`, from the first to the last significant
+    // token of the node.
+    toks: Range<usize>,
+    parent: Option<usize>,
+    // Elements of an array, properties of an object, or the value of a
+    // property.
+    children: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cst<'a> {
+    src: &'a str,
+    toks: Vec<Tok>,
+    nodes: Vec<NodeData>,
+    root: Option<usize>,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> Cst<'a> {
+    pub fn parse(src: &'a str, dialect: Dialect) -> Self {
+        let mut p = Parser {
+            cst: Cst {
+                src,
+                toks: Lexer::new(src, dialect).collect(),
+                nodes: Vec::new(),
+                root: None,
+                errors: Vec::new(),
+            },
+            pos: 0,
+            trailing_comma: dialect.allow_trailing_comma,
+        };
+        p.cst.root = p.value(None);
+        if p.cst.root.is_none() {
+            p.error_at_next("expected a value");
+        }
+        while let Some(i) = p.peek() {
+            p.error(i, "trailing characters");
+            p.pos = i + 1;
+        }
+        p.cst
+    }
+
+    pub fn src(&self) -> &'a str {
+        self.src
+    }
+
+    /// The top-level value, if there is one.
+    pub fn root(&self) -> Option<Node<'_, 'a>> {
+        self.root.map(|id| self.node(id))
+    }
+
+    /// Everything that went wrong while parsing, in source order. The
+    /// document is well-formed if this is empty.
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    fn node(&self, id: usize) -> Node<'_, 'a> {
+        Node { cst: self, id }
+    }
+
+    fn text(&self, tok: usize) -> &'a str {
+        &self.src[self.toks[tok].span.clone()]
+    }
+
+    // The span of the first significant token in `toks`, if it's a comma.
+    fn comma_in<'t>(&self, mut toks: impl Iterator<Item = &'t Tok>) -> Option<Range<usize>> {
+        let t = toks.find(|t| !t.kind.is_trivia() && t.kind != Kind::Error)?;
+        opt_extract!(t.kind, Kind::Comma => Some(t.span.clone()))
+    }
+
+    fn newline_in(&self, r: Range<usize>) -> bool {
+        self.src.as_bytes()[r].contains(&b'\n')
+    }
+}
+
+impl core::fmt::Display for Cst<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for i in 0..self.toks.len() {
+            tri!(f.write_str(self.text(i)));
+        }
+        Ok(())
+    }
+}
+
+struct Parser<'a> {
+    cst: Cst<'a>,
+    pos: usize,
+    // From `Dialect::allow_trailing_comma`.
+    trailing_comma: bool,
+}
+
+impl Parser<'_> {
+    fn error(&mut self, tok: usize, message: &'static str) {
+        let span = self.cst.toks[tok].span.clone();
+        self.cst.errors.push(SyntaxError { span, message });
+    }
+
+    fn error_at_next(&mut self, message: &'static str) {
+        let at = match self.peek() {
+            Some(i) => self.cst.toks[i].span.clone(),
+            None => self.cst.src.len()..self.cst.src.len(),
+        };
+        self.cst.errors.push(SyntaxError { span: at, message });
+    }
+
+    // Index of the next significant token, skipping (and reporting) garbage.
+    fn peek(&mut self) -> Option<usize> {
+        while let Some(t) = self.cst.toks.get(self.pos) {
+            match t.kind {
+                k if k.is_trivia() && t.ok => {}
+                k if k.is_trivia() => self.error(self.pos, "unterminated comment"),
+                Kind::Error => self.error(self.pos, "unexpected character"),
+                _ => return Some(self.pos),
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    fn peek_kind(&mut self) -> Option<Kind> {
+        self.peek().map(|i| self.cst.toks[i].kind)
+    }
+
+    fn push(&mut self, kind: NodeKind, first: usize, parent: Option<usize>) -> usize {
+        let id = self.cst.nodes.len();
+        self.cst.nodes.push(NodeData {
+            kind,
+            toks: first..first + 1,
+            parent,
+            children: Vec::new(),
+        });
+        if let Some(p) = parent {
+            self.cst.nodes[p].children.push(id);
+        }
+        id
+    }
+
+    // Extend `id` to cover everything consumed so far.
+    fn close(&mut self, id: usize) {
+        let last = (0..self.pos).rev().find(|&i| {
+            let k = self.cst.toks[i].kind;
+            !k.is_trivia() && k != Kind::Error
+        });
+        let n = &mut self.cst.nodes[id];
+        n.toks.end = last.map_or(n.toks.end, |l| l + 1).max(n.toks.end);
+    }
+
+    fn value(&mut self, parent: Option<usize>) -> Option<usize> {
+        let i = self.peek()?;
+        let t = &self.cst.toks[i];
+        let (kind, ok) = (t.kind, t.ok);
+        let node = match kind {
+            Kind::ObjectBegin => return Some(self.container(parent, true)),
+            Kind::ArrayBegin => return Some(self.container(parent, false)),
+            Kind::Null => NodeKind::Null,
+            Kind::True | Kind::False => NodeKind::Bool,
+            Kind::Num => NodeKind::Number,
+            Kind::Str => NodeKind::String,
+            _ => return None,
+        };
+        if !ok {
+            self.error(
+                i,
+                match kind {
+                    Kind::Str => "unterminated string",
+                    _ => "invalid number",
+                },
+            );
+        }
+        self.pos = i + 1;
+        Some(self.push(node, i, parent))
+    }
+
+    fn container(&mut self, parent: Option<usize>, obj: bool) -> usize {
+        let (kind, end) = if obj {
+            (NodeKind::Object, Kind::ObjectEnd)
+        } else {
+            (NodeKind::Array, Kind::ArrayEnd)
+        };
+        let id = self.push(kind, self.pos, parent);
+        self.pos += 1;
+        // Whether we're just after an item (as opposed to the opening bracket
+        // or a comma).
+        let mut after_item = false;
+        loop {
+            match self.peek_kind() {
+                None => {
+                    self.error_at_next("unclosed container");
+                    break;
+                }
+                Some(k) if k == end => {
+                    if !after_item
+                        && !self.trailing_comma
+                        && !self.cst.nodes[id].children.is_empty()
+                    {
+                        self.error(self.pos, "trailing comma");
+                    }
+                    self.pos += 1;
+                    break;
+                }
+                // Probably the end of an enclosing container.
+                Some(Kind::ObjectEnd | Kind::ArrayEnd) => {
+                    self.error_at_next("mismatched closing bracket");
+                    break;
+                }
+                Some(Kind::Comma) => {
+                    if !after_item {
+                        self.error(self.pos, "unexpected comma");
+                    }
+                    self.pos += 1;
+                    after_item = false;
+                }
+                Some(_) => {
+                    if after_item {
+                        self.error_at_next("expected a comma");
+                    }
+                    let item = if obj {
+                        self.property(id)
+                    } else {
+                        self.value(Some(id))
+                    };
+                    if item.is_none() {
+                        self.error_at_next("unexpected token");
+                        self.pos += 1;
+                    }
+                    after_item = true;
+                }
+            }
+        }
+        self.close(id);
+        id
+    }
+
+    fn property(&mut self, obj: usize) -> Option<usize> {
+        let i = self.peek()?;
+        if self.cst.toks[i].kind != Kind::Str {
+            return None;
+        }
+        if !self.cst.toks[i].ok {
+            self.error(i, "unterminated string");
+        }
+        let id = self.push(NodeKind::Property, i, Some(obj));
+        self.pos = i + 1;
+        if self.peek_kind() == Some(Kind::Colon) {
+            self.pos += 1;
+            if self.value(Some(id)).is_none() {
+                self.error_at_next("expected a value");
+            }
+        } else {
+            self.error_at_next("expected a colon");
+        }
+        self.close(id);
+        Some(id)
+    }
+}
+
+/// A node of a [`Cst`].
+#[derive(Clone, Copy)]
+pub struct Node<'c, 'a> {
+    cst: &'c Cst<'a>,
+    id: usize,
+}
+
+impl core::fmt::Debug for Node<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Node")
+            .field("kind", &self.kind())
+            .field("span", &self.span())
+            .finish()
+    }
+}
+
+impl PartialEq for Node<'_, '_> {
+    fn eq(&self, o: &Self) -> bool {
+        core::ptr::eq(self.cst, o.cst) && self.id == o.id
+    }
+}
+
+impl<'c, 'a> Node<'c, 'a> {
+    fn data(&self) -> &'c NodeData {
+        &self.cst.nodes[self.id]
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.data().kind
+    }
+
+    /// Byte range from the start of the first token of the node to the end of
+    /// its last. This doesn't include surrounding whitespace or comments (or,
+    /// for properties, the comma).
+    pub fn span(&self) -> Range<usize> {
+        let t = &self.data().toks;
+        self.cst.toks[t.start].span.start..self.cst.toks[t.end - 1].span.end
+    }
+
+    pub fn text(&self) -> &'a str {
+        &self.cst.src[self.span()]
+    }
+
+    pub fn parent(&self) -> Option<Node<'c, 'a>> {
+        self.data().parent.map(|p| self.cst.node(p))
+    }
+
+    /// Elements of an array, properties of an object, or the value of a
+    /// property.
+    pub fn children(&self) -> impl Iterator<Item = Node<'c, 'a>> + 'c {
+        let cst = self.cst;
+        self.data().children.iter().map(move |&c| cst.node(c))
+    }
+
+    /// The element at `i`, if this is an array.
+    pub fn at(&self, i: usize) -> Option<Node<'c, 'a>> {
+        if self.kind() != NodeKind::Array {
+            return None;
+        }
+        self.children().nth(i)
+    }
+
+    /// The property named `key`, if this is an object. If a key is present
+    /// more than once, the last one wins.
+    pub fn property(&self, key: &str) -> Option<Node<'c, 'a>> {
+        if self.kind() != NodeKind::Object {
+            return None;
+        }
+        self.children()
+            .filter(|p| p.key().is_some_and(|k| k == key))
+            .last()
+    }
+
+    /// The value of the property named `key`, if this is an object.
+    pub fn get(&self, key: &str) -> Option<Node<'c, 'a>> {
+        self.property(key)?.value()
+    }
+
+    /// The key, if this is a property.
+    pub fn key(&self) -> Option<Cow<'a, str>> {
+        self.key_token()
+            .map(|t| lex::string_value(self.cst.text(t)))
+    }
+
+    /// Byte range of the key (including quotes), if this is a property.
+    pub fn key_span(&self) -> Option<Range<usize>> {
+        self.key_token().map(|t| self.cst.toks[t].span.clone())
+    }
+
+    fn key_token(&self) -> Option<usize> {
+        match self.kind() {
+            NodeKind::Property => Some(self.data().toks.start),
+            _ => None,
+        }
+    }
+
+    /// The value, if this is a property (and it has one).
+    pub fn value(&self) -> Option<Node<'c, 'a>> {
+        match self.kind() {
+            NodeKind::Property => self.children().next(),
+            _ => None,
+        }
+    }
+
+    /// Follow `path` from this node. Keys step into objects (finding the
+    /// property's value), and indices into arrays.
+    pub fn find(&self, path: &[Segment<'_>]) -> Option<Node<'c, 'a>> {
+        let mut n = *self;
+        for s in path {
+            n = match s {
+                Segment::Key(k) => n.get(k)?,
+                Segment::Index(i) => n.at(*i)?,
+            };
+        }
+        Some(n)
+    }
+
+    /// Look up a value by [JSON Pointer](https://tools.ietf.org/html/rfc6901).
+    pub fn pointer(&self, ptr: &str) -> Option<Node<'c, 'a>> {
+        let mut n = *self;
+        for t in pointer::parse(ptr)? {
+            n = match n.kind() {
+                NodeKind::Object => n.get(&t)?,
+                NodeKind::Array => n.at(pointer::array_index(&t)?)?,
+                _ => return None,
+            };
+        }
+        Some(n)
+    }
+
+    /// Comments directly before this node: those in the run of comments
+    /// leading up to it which isn't interrupted by a blank line, excluding
+    /// one that trails the previous item on its line.
+    pub fn leading_comments(&self) -> Vec<&'a str> {
+        let cst = self.cst;
+        let mut out = Vec::new();
+        let mut i = self.data().toks.start;
+        let mut stop = None;
+        while i > 0 {
+            i -= 1;
+            let t = &cst.toks[i];
+            match t.kind {
+                Kind::Whitespace if cst.text(i).matches('\n').count() > 1 => break,
+                Kind::Whitespace => {}
+                Kind::LineComment | Kind::BlockComment => out.push(i),
+                _ => {
+                    stop = Some(i);
+                    break;
+                }
+            }
+        }
+        out.reverse();
+        if let (Some(s), Some(&first)) = (stop, out.first()) {
+            let opens = matches!(
+                cst.toks[s].kind,
+                Kind::ObjectBegin | Kind::ArrayBegin | Kind::Colon
+            );
+            let gap = cst.toks[s].span.end..cst.toks[first].span.start;
+            if !opens && !cst.newline_in(gap) {
+                out.remove(0);
+            }
+        }
+        out.into_iter().map(|i| cst.text(i)).collect()
+    }
+
+    /// A comment after this node (and its comma, if any) on the same line.
+    pub fn trailing_comment(&self) -> Option<&'a str> {
+        self.trailing_comment_span().map(|r| &self.cst.src[r])
+    }
+
+    pub(crate) fn trailing_comment_span(&self) -> Option<Range<usize>> {
+        let cst = self.cst;
+        let mut comma = false;
+        for i in self.data().toks.end..cst.toks.len() {
+            match cst.toks[i].kind {
+                Kind::Whitespace if cst.text(i).contains('\n') => return None,
+                Kind::Whitespace => {}
+                Kind::Comma if !comma => comma = true,
+                Kind::LineComment | Kind::BlockComment => return Some(cst.toks[i].span.clone()),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// The comma directly after this node, if there is one.
+    pub(crate) fn comma_after(&self) -> Option<Range<usize>> {
+        let toks = &self.cst.toks[self.data().toks.end..];
+        self.cst.comma_in(toks.iter())
+    }
+
+    /// The comma directly before this node, if there is one.
+    pub(crate) fn comma_before(&self) -> Option<Range<usize>> {
+        let toks = &self.cst.toks[..self.data().toks.start];
+        self.cst.comma_in(toks.iter().rev())
+    }
+}
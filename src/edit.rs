@@ -0,0 +1,510 @@
+//! Format-preserving edits.
+//!
+//! [`modify`] works out the smallest text edits which set or remove one value
+//! in a document, leaving everything else (comments, whitespace, key order,
+//! trailing commas) as it was. It's the equivalent of `jsonc-parser`'s
+//! `modify`. New text is produced by [`Writer`], and indented to match its
+//! surroundings.
+//!
+//! ```
+//! # use smoljson::{Value, edit::{apply_edits, modify, ModifyOptions}, location::Segment};
+//! let src = "{\n    // Be loud?\n    \"verbose\": false,\n}";
+//! let opts = ModifyOptions::default().dialect(smoljson::Dialect::LOOSE);
+//! let path = [Segment::Key("verbose".into())];
+//! let edits = modify(src, &path, Some(&Value::Bool(true)), &opts).unwrap();
+//! assert_eq!(apply_edits(src, &edits), "{\n    // Be loud?\n    \"verbose\": true,\n}");
+//!
+//! let path = [Segment::Key("level".into())];
+//! let edits = modify(src, &path, Some(&Value::from(3)), &opts).unwrap();
+//! assert_eq!(
+//!     apply_edits(src, &edits),
+//!     "{\n    // Be loud?\n    \"verbose\": false,\n    \"level\": 3,\n}",
+//! );
+//! ```
+use crate::cst::{Cst, Node, NodeKind};
+use crate::location::Segment;
+use crate::read::Dialect;
+use crate::write::{WriteJson, Writer};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Replace the `len` bytes at `offset` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub offset: usize,
+    pub len: usize,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub(crate) fn new(r: Range<usize>, replacement: String) -> Self {
+        Self {
+            offset: r.start,
+            len: r.end - r.start,
+            replacement,
+        }
+    }
+}
+
+/// Apply non-overlapping `edits` (in any order) to `text`.
+pub fn apply_edits(text: &str, edits: &[Edit]) -> String {
+    let mut sorted = edits.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|e| e.offset);
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for e in sorted {
+        debug_assert!(e.offset >= pos, "overlapping edits");
+        out.push_str(&text[pos..e.offset]);
+        out.push_str(&e.replacement);
+        pos = e.offset + e.len;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// How to lay out newly written text.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions {
+    /// Width of one level of indentation, when indenting with spaces.
+    pub tab_size: usize,
+    /// Indent with spaces (rather than tabs).
+    pub insert_spaces: bool,
+    pub eol: &'static str,
+    /// End the document with `eol`. Only used by [`format`](crate::format).
+    pub insert_final_newline: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            tab_size: 4,
+            insert_spaces: true,
+            eol: "\n",
+            insert_final_newline: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    pub(crate) fn push_indent(&self, s: &mut String, levels: usize) {
+        for _ in 0..levels {
+            if self.insert_spaces {
+                for _ in 0..self.tab_size {
+                    s.push(' ');
+                }
+            } else {
+                s.push('\t');
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModifyOptions {
+    pub format: FormatOptions,
+    pub dialect: Dialect,
+    /// When the path ends at an existing array element, insert the value
+    /// before it rather than replacing it.
+    pub insert: bool,
+}
+
+impl ModifyOptions {
+    pub fn format(mut self, f: FormatOptions) -> Self {
+        self.format = f;
+        self
+    }
+    pub fn dialect(mut self, d: Dialect) -> Self {
+        self.dialect = d;
+        self
+    }
+    pub fn insert(mut self, v: bool) -> Self {
+        self.insert = v;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifyError {
+    /// The path steps into something which isn't an object (for a key) or an
+    /// array (for an index).
+    NotAContainer,
+    /// The path has an index more than one past the end of an array.
+    IndexOutOfBounds,
+    /// The document is too broken where the edit would go.
+    Malformed,
+}
+
+impl core::fmt::Display for ModifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ModifyError::NotAContainer => "path goes through a value of the wrong type",
+            ModifyError::IndexOutOfBounds => "array index out of bounds",
+            ModifyError::Malformed => "document is malformed",
+        })
+    }
+}
+
+/// Compute the edits which set the value at `path` to `value`, or remove it if
+/// `value` is `None`.
+///
+/// Missing objects (and arrays, for index 0) along the path are created. An
+/// index one past the end of an array appends to it. Removing something that
+/// isn't there produces no edits.
+pub fn modify(
+    text: &str,
+    path: &[Segment<'_>],
+    value: Option<&dyn WriteJson>,
+    opts: &ModifyOptions,
+) -> Result<Vec<Edit>, ModifyError> {
+    let cst = Cst::parse(text, opts.dialect);
+    let cx = Cx { src: text, opts };
+    let root = match cst.root() {
+        Some(r) => r,
+        None => {
+            // The only acceptable error is the missing value at the very end.
+            if cst.errors().iter().any(|e| e.span.start != text.len()) {
+                return Err(ModifyError::Malformed);
+            }
+            let v = match value {
+                Some(v) => v,
+                None => return Ok(vec![]),
+            };
+            tri!(check_wrap(path));
+            let mut t = String::new();
+            if !text.is_empty() && !text.ends_with('\n') {
+                t.push_str(opts.format.eol);
+            }
+            t.push_str(&cx.format(&Wrap(path, v), true, ""));
+            return Ok(vec![Edit::new(text.len()..text.len(), t)]);
+        }
+    };
+    let (last, parents) = match path.split_last() {
+        Some(p) => p,
+        None => {
+            let t = value.map_or_else(String::new, |v| cx.format(v, text.contains('\n'), ""));
+            return Ok(vec![Edit::new(root.span(), t)]);
+        }
+    };
+    let mut node = root;
+    for (i, seg) in parents.iter().enumerate() {
+        match tri!(step(node, seg)) {
+            Some(n) => node = n,
+            None => {
+                return match value {
+                    Some(v) => {
+                        tri!(check_wrap(&path[i + 1..]));
+                        cx.set(node, seg, &Wrap(&path[i + 1..], v))
+                    }
+                    None => Ok(vec![]),
+                }
+            }
+        }
+    }
+    match value {
+        Some(v) => cx.set(node, last, v),
+        None => cx.remove(node, last),
+    }
+}
+
+fn step<'c, 'a>(n: Node<'c, 'a>, seg: &Segment<'_>) -> Result<Option<Node<'c, 'a>>, ModifyError> {
+    match (n.kind(), seg) {
+        (NodeKind::Object, Segment::Key(k)) => match n.property(k) {
+            Some(p) => p.value().map(Some).ok_or(ModifyError::Malformed),
+            None => Ok(None),
+        },
+        (NodeKind::Array, Segment::Index(i)) => Ok(n.at(*i)),
+        _ => Err(ModifyError::NotAContainer),
+    }
+}
+
+// The value `.1`, wrapped in objects and arrays to put it at the end of the
+// path `.0`, which must pass `check_wrap`.
+struct Wrap<'p, 'v>(&'p [Segment<'p>], &'v dyn WriteJson);
+
+// New arrays are empty, so only index 0 can be created in them.
+fn check_wrap(path: &[Segment<'_>]) -> Result<(), ModifyError> {
+    if path
+        .iter()
+        .any(|s| matches!(s, Segment::Index(i) if *i != 0))
+    {
+        return Err(ModifyError::IndexOutOfBounds);
+    }
+    Ok(())
+}
+
+impl WriteJson for Wrap<'_, '_> {
+    fn write_json(&self, w: &mut Writer) {
+        match self.0.split_first() {
+            None => self.1.write_json(w),
+            Some((Segment::Key(k), rest)) => {
+                w.object().put(k, &Wrap(rest, self.1));
+            }
+            Some((Segment::Index(_), rest)) => {
+                w.array().put(Wrap(rest, self.1));
+            }
+        }
+    }
+    fn should_include(&self) -> bool {
+        self.1.should_include()
+    }
+}
+
+fn is_blank(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+// The indentation of the line containing `pos`.
+fn line_indent(src: &str, pos: usize) -> &str {
+    let start = src[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let n = src[start..].bytes().take_while(|&b| is_blank(b)).count();
+    &src[start..start + n]
+}
+
+// If `r` is alone on its line(s), extend it to cover the whole line(s),
+// including the newline.
+fn extend_to_lines(src: &str, r: Range<usize>) -> Range<usize> {
+    let start = src[..r.start].rfind('\n').map_or(0, |i| i + 1);
+    if !src.as_bytes()[start..r.start].iter().all(|&b| is_blank(b)) {
+        return r;
+    }
+    let end = r.end + src[r.end..].bytes().take_while(|&b| is_blank(b)).count();
+    let rest = &src[end..];
+    if rest.starts_with("\r\n") {
+        start..end + 2
+    } else if rest.starts_with('\n') {
+        start..end + 1
+    } else if rest.is_empty() {
+        start..end
+    } else {
+        r
+    }
+}
+
+struct Cx<'s, 'o> {
+    src: &'s str,
+    opts: &'o ModifyOptions,
+}
+
+impl Cx<'_, '_> {
+    // Write `v` using our `FormatOptions`: either across lines, with
+    // continuation lines indented relative to `base`, or all on one line (but
+    // spaced the same way).
+    fn format(&self, v: &dyn WriteJson, pretty: bool, base: &str) -> String {
+        let mut w = Writer::new(true);
+        v.write_json(&mut w);
+        let s = w.finish();
+        let mut out = String::with_capacity(s.len());
+        // Strings can't contain raw newlines, so each line of `Writer`'s output
+        // opens a container, closes one, or is a single item.
+        let mut depth = 0;
+        for (i, line) in s.split('\n').enumerate() {
+            let text = line.trim_start();
+            let closes = matches!(text.as_bytes().first(), Some(b'}' | b']'));
+            if closes {
+                depth -= 1;
+            }
+            if i > 0 && pretty {
+                out.push_str(self.opts.format.eol);
+                out.push_str(base);
+                self.opts.format.push_indent(&mut out, depth);
+            } else if i > 0 && !closes && !out.ends_with(&['[', '{'][..]) {
+                out.push(' ');
+            }
+            out.push_str(text);
+            if matches!(text.as_bytes().last(), Some(b'{' | b'[')) {
+                depth += 1;
+            }
+        }
+        out
+    }
+
+    // An array element or object property, written at indentation `indent`.
+    fn item(&self, key: Option<&str>, v: &dyn WriteJson, pretty: bool, indent: &str) -> String {
+        let mut out = String::new();
+        if let Some(k) = key {
+            let mut w = Writer::new(false);
+            k.write_json(&mut w);
+            out.push_str(&w.finish());
+            out.push_str(": ");
+        }
+        out.push_str(&self.format(v, pretty, indent));
+        out
+    }
+
+    fn multiline(&self, n: Node<'_, '_>) -> bool {
+        let mut c = n.parent();
+        while let Some(p) = c {
+            if p.kind() != NodeKind::Property {
+                return p.text().contains('\n');
+            }
+            c = p.parent();
+        }
+        self.src.contains('\n')
+    }
+
+    fn set(
+        &self,
+        c: Node<'_, '_>,
+        seg: &Segment<'_>,
+        v: &dyn WriteJson,
+    ) -> Result<Vec<Edit>, ModifyError> {
+        match (c.kind(), seg) {
+            (NodeKind::Object, Segment::Key(k)) => match c.property(k) {
+                Some(p) => match p.value() {
+                    Some(old) => Ok(vec![self.replace(old, v)]),
+                    None => Err(ModifyError::Malformed),
+                },
+                None => self.append(c, Some(k), v),
+            },
+            (NodeKind::Array, &Segment::Index(i)) => {
+                let len = c.children().count();
+                match c.at(i) {
+                    Some(old) if self.opts.insert => Ok(vec![self.insert_before(c, old, v)]),
+                    Some(old) => Ok(vec![self.replace(old, v)]),
+                    None if i == len => self.append(c, None, v),
+                    None => Err(ModifyError::IndexOutOfBounds),
+                }
+            }
+            _ => Err(ModifyError::NotAContainer),
+        }
+    }
+
+    fn replace(&self, old: Node<'_, '_>, v: &dyn WriteJson) -> Edit {
+        let span = old.span();
+        let indent = line_indent(self.src, span.start);
+        Edit::new(span, self.format(v, self.multiline(old), indent))
+    }
+
+    fn insert_before(&self, c: Node<'_, '_>, old: Node<'_, '_>, v: &dyn WriteJson) -> Edit {
+        let at = old.span().start;
+        let multiline = c.text().contains('\n');
+        let indent = line_indent(self.src, at);
+        let mut t = self.item(None, v, multiline, indent);
+        t.push(',');
+        if multiline {
+            t.push_str(self.opts.format.eol);
+            t.push_str(indent);
+        } else {
+            t.push(' ');
+        }
+        Edit::new(at..at, t)
+    }
+
+    fn append(
+        &self,
+        c: Node<'_, '_>,
+        key: Option<&str>,
+        v: &dyn WriteJson,
+    ) -> Result<Vec<Edit>, ModifyError> {
+        let span = c.span();
+        let close = if c.kind() == NodeKind::Object {
+            '}'
+        } else {
+            ']'
+        };
+        if span.len() < 2 || !c.text().ends_with(close) {
+            return Err(ModifyError::Malformed);
+        }
+        let eol = self.opts.format.eol;
+        let last = match c.children().last() {
+            Some(l) => l,
+            None => {
+                let inner = span.start + 1..span.end - 1;
+                let blank = self.src[inner.clone()].trim().is_empty();
+                let at = if blank {
+                    inner
+                } else {
+                    inner.start..inner.start
+                };
+                let mut t = String::new();
+                if self.src.contains('\n') {
+                    let base = line_indent(self.src, span.start);
+                    let mut indent = String::from(base);
+                    self.opts.format.push_indent(&mut indent, 1);
+                    t.push_str(eol);
+                    t.push_str(&indent);
+                    t.push_str(&self.item(key, v, true, &indent));
+                    if blank {
+                        t.push_str(eol);
+                        t.push_str(base);
+                    }
+                } else {
+                    t.push_str(&self.item(key, v, false, ""));
+                    if !blank {
+                        t.push(' ');
+                    }
+                }
+                return Ok(vec![Edit::new(at, t)]);
+            }
+        };
+        let multiline = c.text().contains('\n');
+        let lspan = last.span();
+        let indent = line_indent(self.src, lspan.start);
+        let mut t = String::new();
+        if multiline {
+            t.push_str(eol);
+            t.push_str(indent);
+        } else {
+            t.push(' ');
+        }
+        t.push_str(&self.item(key, v, multiline, indent));
+        let comment_end = last.trailing_comment_span().map(|r| r.end);
+        Ok(match last.comma_after() {
+            // Keep the trailing comma style.
+            Some(comma) => {
+                t.push(',');
+                // The comment might be before the comma, or after it.
+                let at = comment_end.map_or(comma.end, |e| e.max(comma.end));
+                vec![Edit::new(at..at, t)]
+            }
+            // Don't put the new item (or the comma) after a comment.
+            None => match comment_end {
+                Some(at) => vec![
+                    Edit::new(lspan.end..lspan.end, String::from(",")),
+                    Edit::new(at..at, t),
+                ],
+                None => {
+                    t.insert(0, ',');
+                    vec![Edit::new(lspan.end..lspan.end, t)]
+                }
+            },
+        })
+    }
+
+    fn remove(&self, c: Node<'_, '_>, seg: &Segment<'_>) -> Result<Vec<Edit>, ModifyError> {
+        let node = match (c.kind(), seg) {
+            (NodeKind::Object, Segment::Key(k)) => c.property(k),
+            (NodeKind::Array, &Segment::Index(i)) => c.at(i),
+            _ => return Err(ModifyError::NotAContainer),
+        };
+        let node = match node {
+            Some(n) => n,
+            None => return Ok(vec![]),
+        };
+        let src = self.src;
+        let span = node.span();
+        if let Some(comma) = node.comma_after() {
+            let r = span.start..comma.end;
+            let lines = extend_to_lines(src, r.clone());
+            let r = if lines != r {
+                lines
+            } else {
+                r.start..r.end + src[r.end..].bytes().take_while(|&b| is_blank(b)).count()
+            };
+            return Ok(vec![Edit::new(r, String::new())]);
+        }
+        if let Some(comma) = node.comma_before() {
+            // Unless there are comments between the two, which need to stay.
+            if src[comma.end..span.start].trim().is_empty() {
+                return Ok(vec![Edit::new(comma.start..span.end, String::new())]);
+            }
+            return Ok(vec![
+                Edit::new(comma, String::new()),
+                Edit::new(extend_to_lines(src, span), String::new()),
+            ]);
+        }
+        Ok(vec![Edit::new(extend_to_lines(src, span), String::new())])
+    }
+}
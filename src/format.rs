@@ -0,0 +1,225 @@
+//! Formatting which produces text edits, for editors.
+//!
+//! [`format`] only touches the whitespace between tokens, so comments (when
+//! the dialect allows them) and everything else stay where they are. This
+//! follows `jsonc-parser`'s `format`.
+//!
+//! ```
+//! # use smoljson::{Dialect, edit::{apply_edits, FormatOptions}, format::format};
+//! let src = "{\"a\":1, // one\n\"b\":[ ]}";
+//! let edits = format(src, None, Dialect::LOOSE, &FormatOptions::default());
+//! assert_eq!(apply_edits(src, &edits), "{\n    \"a\": 1, // one\n    \"b\": []\n}");
+//! ```
+use crate::edit::{Edit, FormatOptions};
+use crate::lex::{Kind, Lexer};
+use crate::read::Dialect;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+fn is_eol(b: u8) -> bool {
+    b == b'\n' || b == b'\r'
+}
+
+/// Compute the edits which format `text`.
+///
+/// If `range` is given, only edits overlapping it are made. The lines it
+/// touches are formatted on their own, starting from the indentation of the
+/// first of them.
+///
+/// If the text is malformed, formatting stops at the first problem.
+pub fn format(
+    text: &str,
+    range: Option<Range<usize>>,
+    dialect: Dialect,
+    opts: &FormatOptions,
+) -> Vec<Edit> {
+    let bs = text.as_bytes();
+    let (start, end) = match &range {
+        Some(r) => {
+            let mut s = r.start.min(bs.len());
+            while s > 0 && !is_eol(bs[s - 1]) {
+                s -= 1;
+            }
+            let mut e = r.end.clamp(s, bs.len());
+            while e < bs.len() && !is_eol(bs[e]) {
+                e += 1;
+            }
+            (s, e)
+        }
+        None => (0, bs.len()),
+    };
+    let part = &text[start..end];
+    let base = if range.is_some() {
+        indent_level(part, opts)
+    } else {
+        0
+    };
+    let mut f = Formatter {
+        text,
+        start,
+        len: end - start,
+        range,
+        opts,
+        base,
+        level: 0,
+        failed: false,
+        edits: Vec::new(),
+    };
+    f.run(Lexer::new(part, dialect));
+    f.edits
+}
+
+// Indentation of the first line of `s`, in levels.
+fn indent_level(s: &str, opts: &FormatOptions) -> usize {
+    let mut width = 0;
+    for b in s.bytes() {
+        match b {
+            b' ' => width += 1,
+            b'\t' => width += opts.tab_size,
+            _ => break,
+        }
+    }
+    width / opts.tab_size.max(1)
+}
+
+struct Formatter<'t, 'o> {
+    text: &'t str,
+    // The part of `text` being formatted.
+    start: usize,
+    len: usize,
+    range: Option<Range<usize>>,
+    opts: &'o FormatOptions,
+    base: usize,
+    level: isize,
+    // Set at the first malformed token, after which nothing is changed.
+    failed: bool,
+    edits: Vec<Edit>,
+}
+
+impl Formatter<'_, '_> {
+    fn newline(&self) -> String {
+        let mut s = String::from(self.opts.eol);
+        let levels = (self.base as isize + self.level).max(0) as usize;
+        self.opts.push_indent(&mut s, levels);
+        s
+    }
+
+    // Replace the whitespace between `s` and `e` (relative to the formatted
+    // part) with `with`.
+    fn edit(&mut self, with: String, s: usize, e: usize) {
+        let (s, e) = (self.start + s, self.start + e);
+        let in_range = match &self.range {
+            Some(r) => s < r.end && e > r.start,
+            None => true,
+        };
+        if !self.failed && in_range && self.text[s..e] != with {
+            self.edits.push(Edit::new(s..e, with));
+        }
+    }
+
+    fn run(&mut self, mut lex: Lexer<'_>) {
+        // The next non-whitespace token, and whether there was a line break
+        // before it.
+        let src = lex.src();
+        let mut next = || {
+            let mut line_break = false;
+            for t in lex.by_ref() {
+                if t.kind != Kind::Whitespace {
+                    return (Some(t), line_break);
+                }
+                line_break |= src[t.span].bytes().any(is_eol);
+            }
+            (None, line_break)
+        };
+        let mut first = match next().0 {
+            Some(t) => t,
+            None => return,
+        };
+        let mut indent = String::new();
+        self.opts.push_indent(&mut indent, self.base);
+        self.edit(indent, 0, first.span.start);
+        loop {
+            self.failed |= !first.ok;
+            let mut first_end = first.span.end;
+            let (mut second, mut line_break) = next();
+            let mut with = String::new();
+            let mut needs_line_break = false;
+            // Comments on the same line stay there, one space away.
+            while let (Some(c), false) = (&second, line_break) {
+                if !matches!(c.kind, Kind::LineComment | Kind::BlockComment) {
+                    break;
+                }
+                self.failed |= !c.ok;
+                self.edit(String::from(" "), first_end, c.span.start);
+                first_end = c.span.end;
+                needs_line_break = c.kind == Kind::LineComment;
+                with = if needs_line_break {
+                    self.newline()
+                } else {
+                    String::new()
+                };
+                let n = next();
+                second = n.0;
+                line_break = n.1;
+            }
+            if let Some(t) = &second {
+                self.failed |= !t.ok;
+            }
+            let second_kind = second.as_ref().map(|t| t.kind);
+            let is_comment = matches!(second_kind, Some(Kind::LineComment | Kind::BlockComment));
+            match (first.kind, second_kind) {
+                (Kind::ObjectBegin, Some(Kind::ObjectEnd))
+                | (Kind::ArrayBegin, Some(Kind::ArrayEnd)) => {}
+                (_, Some(Kind::ObjectEnd | Kind::ArrayEnd)) => {
+                    self.level -= 1;
+                    with = self.newline();
+                }
+                (Kind::ObjectBegin | Kind::ArrayBegin, _) => {
+                    self.level += 1;
+                    with = self.newline();
+                }
+                (Kind::Comma | Kind::LineComment, _) => with = self.newline(),
+                (Kind::BlockComment, _) if line_break => with = self.newline(),
+                (Kind::BlockComment | Kind::Colon, _) => {
+                    if !needs_line_break {
+                        with = String::from(" ");
+                    }
+                }
+                (Kind::Str, Some(Kind::Colon)) => {
+                    if !needs_line_break {
+                        with = String::new();
+                    }
+                }
+                (k, _) if k.is_scalar() || matches!(k, Kind::ObjectEnd | Kind::ArrayEnd) => {
+                    if is_comment {
+                        if !needs_line_break {
+                            with = String::from(" ");
+                        }
+                    } else if !matches!(second_kind, Some(Kind::Comma) | None) {
+                        self.failed = true;
+                    }
+                }
+                _ => self.failed = true,
+            }
+            if line_break && is_comment {
+                with = self.newline();
+            }
+            let second = match second {
+                Some(t) => t,
+                None => {
+                    // Only the end of the whole document gets a final newline.
+                    let with = if self.opts.insert_final_newline && self.range.is_none() {
+                        String::from(self.opts.eol)
+                    } else {
+                        String::new()
+                    };
+                    self.edit(with, first_end, self.len);
+                    return;
+                }
+            };
+            self.edit(with, first_end, second.span.start);
+            first = second;
+        }
+    }
+}
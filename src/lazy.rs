@@ -0,0 +1,231 @@
+//! On-demand access to large documents which are only sparsely read.
+//!
+//! A [`LazyDocument`] doesn't parse anything up front. Each lookup scans the
+//! source only as far as it needs to, and remembers where every child value of
+//! each container it passes through starts, so looking up the same (or an
+//! earlier) child again doesn't rescan anything. Only leaves which are
+//! actually requested (with [`LazyValue::value`]) are turned into [`Value`]s.
+//!
+//! Arrays are scanned up to the requested index. Objects are scanned to the
+//! end on the first key lookup (since, as with `Value`, the last duplicate key
+//! wins), which also builds a map from keys to offsets, so later lookups in
+//! the same object take `O(log n)`.
+//!
+//! Note that this means parts of the document which are never scanned are
+//! never checked for errors.
+//!
+//! ```
+//! # use smoljson::{json, LazyDocument};
+//! let doc = LazyDocument::new(r#"{"meta": {"version": 3}, "items": [10, 20, 30]}"#);
+//! let ver = doc.get("meta").unwrap().unwrap().get("version").unwrap().unwrap();
+//! assert_eq!(ver.value().unwrap(), json!(3));
+//! let items = doc.get("items").unwrap().unwrap();
+//! assert_eq!(items.at(2).unwrap().unwrap().value().unwrap(), json!(30));
+//! assert!(items.at(3).unwrap().is_none());
+//! ```
+use crate::read::{Dialect, Reader, Result, Token};
+use crate::value::Value;
+use alloc::borrow::Cow;
+use alloc::collections::btree_map::{BTreeMap, Entry};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+pub struct LazyDocument<'a> {
+    src: &'a str,
+    dialect: Dialect,
+    // Keyed by the offset of the container's opening bracket.
+    index: RefCell<BTreeMap<usize, Container<'a>>>,
+}
+
+struct Container<'a> {
+    is_obj: bool,
+    // Only filled in for objects, with the offset of each key's value (the
+    // last one, for duplicates).
+    keys: BTreeMap<Cow<'a, str>, usize>,
+    offsets: Vec<usize>,
+    // Where to continue scanning from: either just after the opening bracket,
+    // or the start of the last child in `offsets` (which we don't skip over
+    // until we need what comes after it).
+    resume: usize,
+    done: bool,
+}
+
+/// A cursor to a value inside a [`LazyDocument`].
+#[derive(Clone, Copy)]
+pub struct LazyValue<'d, 'a> {
+    doc: &'d LazyDocument<'a>,
+    offset: usize,
+}
+
+impl<'a> LazyDocument<'a> {
+    /// Create a document which uses the [default `Dialect`](Dialect::DEFAULT).
+    pub fn new(src: &'a str) -> Self {
+        Self::with_dialect(src, Dialect::DEFAULT)
+    }
+
+    /// Create a document with a specific dialect.
+    pub fn with_dialect(src: &'a str, dialect: Dialect) -> Self {
+        Self {
+            src,
+            dialect,
+            index: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// The top-level value.
+    pub fn root(&self) -> Result<LazyValue<'_, 'a>> {
+        let mut r = self.reader_at(0);
+        let offset = match tri!(r.skipnpeek()) {
+            Some(_) => r.position(),
+            None => return Err(r.err()),
+        };
+        Ok(LazyValue { doc: self, offset })
+    }
+
+    /// Shorthand for `self.root()?.get(key)`.
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'_, 'a>>> {
+        tri!(self.root()).get(key)
+    }
+
+    /// Shorthand for `self.root()?.at(index)`.
+    pub fn at(&self, index: usize) -> Result<Option<LazyValue<'_, 'a>>> {
+        tri!(self.root()).at(index)
+    }
+
+    /// Parse the whole document. Unlike the other accessors, this checks that
+    /// nothing follows the top-level value.
+    pub fn value(&self) -> Result<Value<'a>> {
+        Value::from_str_with(self.src, self.dialect)
+    }
+
+    fn reader_at(&self, pos: usize) -> Reader<'a> {
+        let mut r = Reader::with_dialect(self.src, self.dialect);
+        r.seek(pos);
+        r
+    }
+
+    // Find the child of the container at `offset` matching `want`, scanning
+    // more of the container if needed. Returns `Ok(None)` if `offset` isn't a
+    // container of the right kind, or it has no such child.
+    fn child(&self, offset: usize, want: Child<'_>) -> Result<Option<usize>> {
+        let mut index = self.index.borrow_mut();
+        let c = match index.entry(offset) {
+            Entry::Occupied(o) => o.into_mut(),
+            Entry::Vacant(v) => {
+                let mut r = self.reader_at(offset);
+                let is_obj = match tri!(r.next()) {
+                    Token::ObjectBegin => true,
+                    Token::ArrayBegin => false,
+                    _ => return Ok(None),
+                };
+                v.insert(Container {
+                    is_obj,
+                    keys: BTreeMap::new(),
+                    offsets: Vec::new(),
+                    resume: r.position(),
+                    done: false,
+                })
+            }
+        };
+        if c.is_obj != matches!(want, Child::Key(_)) {
+            return Ok(None);
+        }
+        match want {
+            Child::Key(k) => {
+                // The last duplicate wins (as in `Value`), so the whole object
+                // has to be scanned before we can answer.
+                while !c.done {
+                    tri!(self.scan_next(c));
+                }
+                Ok(c.keys.get(k).copied())
+            }
+            Child::Index(i) => {
+                while i >= c.offsets.len() && !c.done {
+                    tri!(self.scan_next(c));
+                }
+                Ok(c.offsets.get(i).copied())
+            }
+        }
+    }
+
+    fn scan_next(&self, c: &mut Container<'a>) -> Result<()> {
+        let mut r = self.reader_at(c.resume);
+        let more = if c.offsets.is_empty() {
+            let close = if c.is_obj { b'}' } else { b']' };
+            tri!(r.skipnpeek()) != Some(close)
+        } else if c.is_obj {
+            tri!(r.skip_value());
+            tri!(r.comma_or_obj_end())
+        } else {
+            tri!(r.skip_value());
+            tri!(r.comma_or_array_end())
+        };
+        if !more {
+            c.done = true;
+            return Ok(());
+        }
+        let key = if c.is_obj {
+            let k = tri!(r.key());
+            tri!(r.colon());
+            Some(k)
+        } else {
+            None
+        };
+        // Cheap sanity check, since we don't look at the value itself yet.
+        if matches!(tri!(r.skipnpeek()), None | Some(b',' | b':' | b']' | b'}')) {
+            return Err(r.err());
+        }
+        if let Some(k) = key {
+            c.keys.insert(k, r.position());
+        }
+        c.offsets.push(r.position());
+        c.resume = r.position();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Child<'k> {
+    Key(&'k str),
+    Index(usize),
+}
+
+impl<'d, 'a> LazyValue<'d, 'a> {
+    /// Byte offset of the start of this value in the source.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.doc.src.as_bytes()[self.offset] == b'{'
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.doc.src.as_bytes()[self.offset] == b'['
+    }
+
+    /// Look up `key`, if this is an object. If the key is present more than
+    /// once, the last is used, so this scans the whole object the first time
+    /// it's called on it. After that, it's a map lookup.
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'d, 'a>>> {
+        let found = tri!(self.doc.child(self.offset, Child::Key(key)));
+        Ok(found.map(|offset| LazyValue {
+            doc: self.doc,
+            offset,
+        }))
+    }
+
+    /// Look up the `index`th element, if this is an array.
+    pub fn at(&self, index: usize) -> Result<Option<LazyValue<'d, 'a>>> {
+        let found = tri!(self.doc.child(self.offset, Child::Index(index)));
+        Ok(found.map(|offset| LazyValue {
+            doc: self.doc,
+            offset,
+        }))
+    }
+
+    /// Parse this value.
+    pub fn value(&self) -> Result<Value<'a>> {
+        Value::from_reader(&mut self.doc.reader_at(self.offset))
+    }
+}
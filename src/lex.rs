@@ -0,0 +1,235 @@
+//! A lossless, fault-tolerant lexer.
+//!
+//! Unlike the [`Reader`](crate::Reader), this never fails: it splits the whole
+//! input into tokens (including whitespace and comments), and anything it
+//! can't make sense of becomes an error token. This is what the tooling-style
+//! APIs (which need to work on incomplete or broken documents) are built on.
+use crate::read::Dialect;
+use core::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    ObjectBegin,
+    ObjectEnd,
+    ArrayBegin,
+    ArrayEnd,
+    Colon,
+    Comma,
+    Str,
+    Num,
+    True,
+    False,
+    Null,
+    LineComment,
+    BlockComment,
+    Whitespace,
+    Error,
+}
+
+impl Kind {
+    /// Whitespace and comments.
+    pub(crate) fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Kind::Whitespace | Kind::LineComment | Kind::BlockComment
+        )
+    }
+    /// Strings, numbers and keywords.
+    pub(crate) fn is_scalar(self) -> bool {
+        matches!(
+            self,
+            Kind::Str | Kind::Num | Kind::True | Kind::False | Kind::Null
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Tok {
+    pub(crate) kind: Kind,
+    pub(crate) span: Range<usize>,
+    /// False for unterminated strings and comments, and malformed numbers.
+    /// (`Kind::Error` tokens are always `false`).
+    pub(crate) ok: bool,
+}
+
+#[derive(Clone)]
+pub(crate) struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+    dialect: Dialect,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(src: &'a str, dialect: Dialect) -> Self {
+        Self {
+            src,
+            pos: 0,
+            dialect,
+        }
+    }
+
+    pub(crate) fn src(&self) -> &'a str {
+        self.src
+    }
+
+    fn scan_while(&self, mut p: usize, f: impl Fn(u8) -> bool) -> usize {
+        let bs = self.src.as_bytes();
+        while p < bs.len() && f(bs[p]) {
+            p += 1;
+        }
+        p
+    }
+
+    fn scan_string(&self, start: usize) -> (usize, bool) {
+        let bs = self.src.as_bytes();
+        let mut p = start + 1;
+        loop {
+            match bs.get(p) {
+                Some(b'"') => return (p + 1, true),
+                Some(b'\\') if p + 1 < bs.len() && bs[p + 1] != b'\n' => p += 2,
+                // Unterminated strings stop at the end of the line, so that
+                // one missing quote doesn't swallow the rest of the document.
+                Some(b'\n') | None => return (p, false),
+                Some(_) => p += 1,
+            }
+        }
+    }
+
+    fn scan_number(&self, start: usize) -> (usize, bool) {
+        let end = self.scan_while(start, |b| {
+            matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        });
+        (end, is_valid_number(&self.src.as_bytes()[start..end]))
+    }
+}
+
+/// Checks against the JSON number grammar:
+/// `-? (0 | [1-9][0-9]*) (\.[0-9]+)? ([eE][+-]?[0-9]+)?`
+pub(crate) fn is_valid_number(bs: &[u8]) -> bool {
+    number_end(bs, 0) == Some(bs.len())
+}
+
+const fn digits(bs: &[u8], mut p: usize) -> usize {
+    while p < bs.len() && bs[p].is_ascii_digit() {
+        p += 1;
+    }
+    p
+}
+
+/// `bs[p]`, or 0 if `p` is past the end.
+pub(crate) const fn byte_at(bs: &[u8], p: usize) -> u8 {
+    if p < bs.len() {
+        bs[p]
+    } else {
+        0
+    }
+}
+
+/// The end of the number starting at `p`, or `None` if there isn't a valid
+/// one there. This only looks at the grammar, so the caller has to check
+/// what follows it. It's a `const fn` so that
+/// [`validate`](crate::validate::validate) can use it too.
+pub(crate) const fn number_end(bs: &[u8], mut p: usize) -> Option<usize> {
+    if byte_at(bs, p) == b'-' {
+        p += 1;
+    }
+    match byte_at(bs, p) {
+        b'0' => p += 1,
+        b'1'..=b'9' => p = digits(bs, p),
+        _ => return None,
+    }
+    if byte_at(bs, p) == b'.' {
+        let e = digits(bs, p + 1);
+        if e == p + 1 {
+            return None;
+        }
+        p = e;
+    }
+    if matches!(byte_at(bs, p), b'e' | b'E') {
+        p += 1;
+        if matches!(byte_at(bs, p), b'+' | b'-') {
+            p += 1;
+        }
+        let e = digits(bs, p);
+        if e == p {
+            return None;
+        }
+        p = e;
+    }
+    Some(p)
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Tok;
+    fn next(&mut self) -> Option<Tok> {
+        let bs = self.src.as_bytes();
+        let start = self.pos;
+        let &b = bs.get(start)?;
+        let (kind, end, ok) = match b {
+            b'{' => (Kind::ObjectBegin, start + 1, true),
+            b'}' => (Kind::ObjectEnd, start + 1, true),
+            b'[' => (Kind::ArrayBegin, start + 1, true),
+            b']' => (Kind::ArrayEnd, start + 1, true),
+            b':' => (Kind::Colon, start + 1, true),
+            b',' => (Kind::Comma, start + 1, true),
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                let end = crate::scan::skip_ws(bs, start);
+                (Kind::Whitespace, end, true)
+            }
+            b'"' => {
+                let (end, ok) = self.scan_string(start);
+                (Kind::Str, end, ok)
+            }
+            b'-' | b'0'..=b'9' => {
+                let (end, ok) = self.scan_number(start);
+                (Kind::Num, end, ok)
+            }
+            b'/' if self.dialect.allow_comments && bs.get(start + 1) == Some(&b'/') => {
+                let end = crate::scan::find_newline(bs, start);
+                (Kind::LineComment, end, true)
+            }
+            b'/' if self.dialect.allow_comments && bs.get(start + 1) == Some(&b'*') => {
+                let p = crate::scan::find_block_end(bs, start + 2);
+                if p + 1 < bs.len() {
+                    (Kind::BlockComment, p + 2, true)
+                } else {
+                    (Kind::BlockComment, bs.len(), false)
+                }
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                let end = self.scan_while(start, |b| b.is_ascii_alphanumeric() || b == b'_');
+                let kind = match &bs[start..end] {
+                    b"true" => Kind::True,
+                    b"false" => Kind::False,
+                    b"null" => Kind::Null,
+                    _ => Kind::Error,
+                };
+                (kind, end, kind != Kind::Error)
+            }
+            _ => {
+                // A single (possibly multi-byte) character.
+                let len = self.src[start..].chars().next().map_or(1, char::len_utf8);
+                (Kind::Error, start + len, false)
+            }
+        };
+        self.pos = end;
+        Some(Tok {
+            kind,
+            span: start..end,
+            ok,
+        })
+    }
+}
+
+/// The contents of a string token, unescaped if possible. For unterminated or
+/// otherwise broken strings, this is the raw text between the quotes.
+pub(crate) fn string_value(tok: &str) -> alloc::borrow::Cow<'_, str> {
+    use alloc::borrow::Cow;
+    if tok.len() >= 2 && tok.ends_with('"') {
+        if let Ok(s) = crate::Reader::with_dialect(tok, Dialect::STRICT).read_str() {
+            return s;
+        }
+    }
+    let inner = tok.strip_prefix('"').unwrap_or(tok);
+    Cow::Borrowed(inner.strip_suffix('"').unwrap_or(inner))
+}
@@ -35,11 +35,15 @@
 //! [`Dialect::DEFAULT`]'s documentation for details.
 
 #![no_std]
+#![deny(unsafe_code)]
 #![allow(dead_code)]
 
 #[doc(hidden)]
 pub extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 #[doc(hidden)]
 pub use core;
 
@@ -66,10 +70,34 @@ macro_rules! tri {
 #[macro_use]
 mod mac;
 
+pub mod arena;
+pub mod context;
+pub mod cst;
+pub mod edit;
+pub mod format;
+pub mod lazy;
+mod lex;
+pub mod line_index;
+pub mod location;
+#[cfg(feature = "std")]
+pub mod ndjson;
+pub mod pointer;
 pub mod read;
+// Only public for `benches/scan.rs`.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod scan;
+#[cfg(not(feature = "bench"))]
+mod scan;
+pub mod spanned;
+pub mod tape;
+pub mod token;
+pub mod validate;
 pub mod value;
 pub mod write;
-pub use read::{Dialect, Error, Reader};
+pub use context::ParseContext;
+pub use lazy::LazyDocument;
+pub use read::{strip_comments, strip_comments_with, ArrayItems, Dialect, Error, Reader};
 pub use value::Value;
 
 pub type ValOwn = Value<'static>;
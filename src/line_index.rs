@@ -0,0 +1,138 @@
+//! Conversions between byte offsets and line/column positions.
+//!
+//! Everything in this crate which reports a location (spans, offsets, edits)
+//! does so in bytes. Editors and the Language Server Protocol want a line and
+//! column instead, where LSP columns are usually counted in UTF-16 code units.
+//! A [`LineIndex`] is built once per document and does the translation.
+//!
+//! ```
+//! # use smoljson::line_index::{Encoding, LineCol, LineIndex};
+//! let src = "{\n  \"\u{1f600}\": 1\n}";
+//! let idx = LineIndex::new(src);
+//! let one = src.find('1').unwrap();
+//! assert_eq!(idx.line_col(one, Encoding::Utf8), Some(LineCol { line: 1, col: 10 }));
+//! assert_eq!(idx.line_col(one, Encoding::Utf16), Some(LineCol { line: 1, col: 8 }));
+//! assert_eq!(idx.line_col(one, Encoding::Char), Some(LineCol { line: 1, col: 7 }));
+//! assert_eq!(idx.offset(LineCol { line: 1, col: 8 }, Encoding::Utf16), Some(one));
+//! ```
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// What columns are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Bytes.
+    Utf8,
+    /// UTF-16 code units (the LSP default).
+    Utf16,
+    /// Unicode scalar values (`char`s).
+    Char,
+}
+
+impl Encoding {
+    fn len(self, s: &str) -> usize {
+        match self {
+            Encoding::Utf8 => s.len(),
+            Encoding::Utf16 => s.chars().map(char::len_utf16).sum(),
+            Encoding::Char => s.chars().count(),
+        }
+    }
+    fn char_len(self, c: char) -> usize {
+        match self {
+            Encoding::Utf8 => c.len_utf8(),
+            Encoding::Utf16 => c.len_utf16(),
+            Encoding::Char => 1,
+        }
+    }
+}
+
+/// A zero-based line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Line start offsets for a document. Lines end at `\n`, `\r\n` or a lone
+/// `\r`, like in LSP.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let bs = text.as_bytes();
+        let mut starts = vec![0];
+        for (i, &b) in bs.iter().enumerate() {
+            if b == b'\n' || (b == b'\r' && bs.get(i + 1) != Some(&b'\n')) {
+                starts.push(i + 1);
+            }
+        }
+        Self { text, starts }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Number of lines. An empty document (or one ending with a line break)
+    /// has an empty last line.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Byte range of `line`, without its line break.
+    pub fn line_span(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.starts.get(line)?;
+        let next = self
+            .starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.text.len());
+        let text = &self.text[start..next];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        Some(start..start + text.len())
+    }
+
+    /// The position of byte `offset`. `None` if it's past the end of the
+    /// text, or not on a `char` boundary.
+    pub fn line_col(&self, offset: usize, enc: Encoding) -> Option<LineCol> {
+        if !self.text.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.starts.partition_point(|&s| s <= offset) - 1;
+        let col = enc.len(&self.text[self.starts[line]..offset]);
+        Some(LineCol { line, col })
+    }
+
+    /// The byte offset of `pos`. `None` if the line doesn't exist.
+    ///
+    /// As in LSP, a column past the end of the line means the end of the line.
+    /// A column in the middle of a character (e.g. between the halves of a
+    /// UTF-16 surrogate pair) is rounded down to the start of it.
+    pub fn offset(&self, pos: LineCol, enc: Encoding) -> Option<usize> {
+        let span = self.line_span(pos.line)?;
+        let mut col = 0;
+        for (i, c) in self.text[span.clone()].char_indices() {
+            col += enc.char_len(c);
+            if col > pos.col {
+                return Some(span.start + i);
+            }
+        }
+        Some(span.end)
+    }
+
+    /// Convert a byte range to a range of positions.
+    pub fn range(&self, r: Range<usize>, enc: Encoding) -> Option<Range<LineCol>> {
+        Some(self.line_col(r.start, enc)?..self.line_col(r.end, enc)?)
+    }
+
+    /// Convert a range of positions to a byte range.
+    pub fn offset_range(&self, r: Range<LineCol>, enc: Encoding) -> Option<Range<usize>> {
+        Some(self.offset(r.start, enc)?..self.offset(r.end, enc)?)
+    }
+}
@@ -0,0 +1,215 @@
+//! Where in a document is a given byte offset? Intended for editor features
+//! like completion, which need to know what the cursor is on.
+//!
+//! This is the equivalent of `jsonc-parser`'s `getLocation`. It never fails,
+//! and works on incomplete or otherwise broken documents (which is the usual
+//! state of a document while it's being typed).
+//!
+//! ```
+//! # use smoljson::{Dialect, location::{location, Segment}};
+//! let src = r#"{"server": {"port": 80, "#;
+//! let loc = location(src, src.len(), Dialect::DEFAULT);
+//! assert_eq!(loc.path, [Segment::Key("server".into()), Segment::Key("".into())]);
+//! assert!(loc.at_key);
+//! assert!(loc.matches(&["server", "*"]));
+//! ```
+use crate::lex::{self, Kind, Lexer};
+use crate::read::Dialect;
+use crate::value::Value;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// A property of an object. This is empty when the cursor is somewhere a
+    /// new property would go, but it hasn't been named yet.
+    Key(Cow<'a, str>),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// An object key.
+    Property,
+    String,
+    Number,
+    Bool,
+    Null,
+}
+
+/// The last key or scalar value before (or under) the cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    pub kind: NodeKind,
+    /// Byte range of the token, including quotes for strings and keys.
+    pub span: Range<usize>,
+    /// The parsed value. Keys are given as [`Value::Str`]. `None` if the
+    /// token is malformed (for example, an unterminated string).
+    pub value: Option<Value<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location<'a> {
+    /// Path from the root to the property or element the cursor is in.
+    pub path: Vec<Segment<'a>>,
+    /// The key or scalar the cursor is on or directly after, if any. This is
+    /// reset by punctuation, so e.g. after `"a": ` it's `None`.
+    pub previous: Option<Node<'a>>,
+    /// Whether the cursor is on (or where there should be) an object key,
+    /// rather than a value.
+    pub at_key: bool,
+}
+
+impl Location<'_> {
+    /// Test the path against a pattern, where `"*"` matches any one segment
+    /// and `"**"` matches any number of them. Array indices match their
+    /// decimal representation.
+    pub fn matches(&self, pattern: &[&str]) -> bool {
+        fn go(pat: &[&str], path: &[Segment<'_>]) -> bool {
+            match (pat.split_first(), path.split_first()) {
+                (None, _) => path.is_empty(),
+                (Some((&"**", rest)), _) => {
+                    go(rest, path) || (!path.is_empty() && go(pat, &path[1..]))
+                }
+                (Some(_), None) => false,
+                (Some((&p, pat)), Some((s, path))) => {
+                    let ok = match s {
+                        _ if p == "*" => true,
+                        Segment::Key(k) => k == p,
+                        Segment::Index(i) => p.parse() == Ok(*i),
+                    };
+                    ok && go(pat, path)
+                }
+            }
+        }
+        go(pattern, &self.path)
+    }
+}
+
+// One per open container, parallel to `Location::path`.
+struct Frame {
+    obj: bool,
+    expect_key: bool,
+}
+
+/// Find the location of byte `offset` in `src`. Comments are skipped if the
+/// dialect allows them (otherwise they're ignored as garbage, which usually
+/// works out similarly).
+pub fn location(src: &str, offset: usize, dialect: Dialect) -> Location<'_> {
+    let mut path: Vec<Segment<'_>> = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut previous = None;
+    let mut at_key = false;
+    let toks = Lexer::new(src, dialect).filter(|t| !t.kind.is_trivia() && t.kind != Kind::Error);
+    for t in toks {
+        let Range { start, end } = t.span.clone();
+        let text = &src[start..end];
+        match t.kind {
+            Kind::ObjectBegin | Kind::ArrayBegin => {
+                if offset <= start {
+                    break;
+                }
+                let obj = t.kind == Kind::ObjectBegin;
+                path.push(if obj {
+                    Segment::Key(Cow::Borrowed(""))
+                } else {
+                    Segment::Index(0)
+                });
+                frames.push(Frame {
+                    obj,
+                    expect_key: obj,
+                });
+                previous = None;
+                at_key = obj;
+            }
+            Kind::ObjectEnd | Kind::ArrayEnd => {
+                if offset <= start {
+                    break;
+                }
+                if frames.pop().is_some() {
+                    path.pop();
+                }
+                previous = None;
+                at_key = false;
+            }
+            Kind::Colon => {
+                if offset <= start {
+                    break;
+                }
+                if matches!(
+                    &previous,
+                    Some(Node {
+                        kind: NodeKind::Property,
+                        ..
+                    })
+                ) {
+                    previous = None;
+                    at_key = false;
+                }
+            }
+            Kind::Comma => {
+                if offset <= start {
+                    break;
+                }
+                match (frames.last_mut(), path.last_mut()) {
+                    (Some(f), Some(seg)) if f.obj => {
+                        f.expect_key = true;
+                        *seg = Segment::Key(Cow::Borrowed(""));
+                        at_key = true;
+                    }
+                    (Some(_), Some(Segment::Index(i))) => *i += 1,
+                    _ => {}
+                }
+                previous = None;
+            }
+            Kind::Str if matches!(frames.last(), Some(f) if f.expect_key) => {
+                if offset < start {
+                    break;
+                }
+                let key = lex::string_value(text);
+                if let (Some(f), Some(seg)) = (frames.last_mut(), path.last_mut()) {
+                    f.expect_key = false;
+                    *seg = Segment::Key(key.clone());
+                }
+                previous = Some(Node {
+                    kind: NodeKind::Property,
+                    span: start..end,
+                    value: if t.ok { Some(Value::Str(key)) } else { None },
+                });
+                at_key = true;
+                if offset <= end {
+                    break;
+                }
+            }
+            _ => {
+                if offset < start {
+                    break;
+                }
+                let (kind, value) = match t.kind {
+                    Kind::Str => (NodeKind::String, Value::Str(lex::string_value(text))),
+                    Kind::Num => (
+                        NodeKind::Number,
+                        Value::from_str(text).unwrap_or(Value::Null),
+                    ),
+                    Kind::True => (NodeKind::Bool, Value::Bool(true)),
+                    Kind::False => (NodeKind::Bool, Value::Bool(false)),
+                    _ => (NodeKind::Null, Value::Null),
+                };
+                previous = Some(Node {
+                    kind,
+                    span: start..end,
+                    value: if t.ok { Some(value) } else { None },
+                });
+                if offset <= end {
+                    break;
+                }
+            }
+        }
+    }
+    Location {
+        path,
+        previous,
+        at_key,
+    }
+}
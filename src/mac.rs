@@ -128,3 +128,26 @@ macro_rules! json_expect_expr_comma {
 macro_rules! json_unexpected {
     () => {};
 }
+
+/// A `&'static str` which is checked to be valid JSON at compile time, using
+/// [`validate`](crate::validate::validate). The dialect defaults to
+/// [`Dialect::DEFAULT`](crate::Dialect::DEFAULT).
+///
+/// ```
+/// const CONFIG: &str = smoljson::json_const!("// defaults\n[1, 2]", smoljson::Dialect::LOOSE);
+/// const EMBEDDED: &str = smoljson::json_const!(concat!("{\"a\":", "1}"));
+/// ```
+#[macro_export]
+macro_rules! json_const {
+    ($s:expr) => {
+        $crate::json_const!($s, $crate::Dialect::DEFAULT)
+    };
+    ($s:expr, $dialect:expr) => {{
+        const S: &str = $s;
+        const _: () = match $crate::validate::validate(S, $dialect) {
+            Ok(()) => (),
+            Err(e) => panic!("{}", e.message),
+        };
+        S
+    }};
+}
@@ -0,0 +1,149 @@
+//! Multi-threaded parsing of newline-delimited JSON (NDJSON).
+//!
+//! Requires the `std` feature.
+//!
+//! The input is split at line boundaries into chunks of roughly
+//! [`NdjsonParser::chunk_size`] bytes, which a fixed number of worker threads
+//! take turns parsing (each reusing its own [`Reader`]). Blank lines are
+//! skipped. Errors are reported per line, and their positions are relative to
+//! the start of that line.
+//!
+//! ```
+//! # use smoljson::{json, ndjson::NdjsonParser};
+//! let input = "{\"id\": 1}\n{\"id\": 2}\n\n[3]\n";
+//! let res = NdjsonParser::new(2).parse(input);
+//! let vals = res.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(vals, vec![json!({"id": 1}), json!({"id": 2}), json!([3])]);
+//! ```
+use crate::read::{Dialect, Reader, Result};
+use crate::value::Value;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, Copy)]
+pub struct NdjsonParser {
+    threads: usize,
+    chunk_size: usize,
+    dialect: Dialect,
+}
+
+impl NdjsonParser {
+    /// A parser which uses `threads` worker threads. If `threads` is 0, uses
+    /// [`std::thread::available_parallelism`].
+    pub fn new(threads: usize) -> Self {
+        let threads = if threads == 0 {
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            threads
+        };
+        Self {
+            threads,
+            chunk_size: 1 << 20,
+            dialect: Dialect::DEFAULT,
+        }
+    }
+
+    /// Use `d` when parsing each line.
+    pub fn dialect(mut self, d: Dialect) -> Self {
+        self.dialect = d;
+        self
+    }
+
+    /// Approximate number of bytes handed to a worker at a time. Defaults to
+    /// 1MiB.
+    pub fn chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// Parse every line of `input`, returning the results in input order.
+    pub fn parse<'a>(&self, input: &'a str) -> Vec<Result<Value<'a>>> {
+        let chunks = split(input, self.chunk_size);
+        let next = AtomicUsize::new(0);
+        // Each worker returns the chunks it parsed, tagged with their index.
+        let mut done: Vec<(usize, Vec<Result<Value<'a>>>)> = std::thread::scope(|s| {
+            let workers = (0..self.threads.min(chunks.len()))
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut r = Reader::with_dialect("", self.dialect);
+                        let mut out = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let chunk = match chunks.get(i) {
+                                Some(&(_, c)) => c,
+                                None => return out,
+                            };
+                            let mut vals = Vec::new();
+                            for_lines(chunk, |_, line| vals.push(parse_line(&mut r, line)));
+                            out.push((i, vals));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        });
+        done.sort_unstable_by_key(|&(i, _)| i);
+        done.into_iter().flat_map(|(_, vals)| vals).collect()
+    }
+
+    /// Parse every line of `input`, calling `f` with the byte offset of the
+    /// start of the line and the result as soon as each line is parsed.
+    ///
+    /// `f` is called from the worker threads, in no particular order.
+    pub fn for_each_unordered<'a, F>(&self, input: &'a str, f: F)
+    where
+        F: Fn(usize, Result<Value<'a>>) + Sync,
+    {
+        let chunks = split(input, self.chunk_size);
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..self.threads.min(chunks.len()) {
+                s.spawn(|| {
+                    let mut r = Reader::with_dialect("", self.dialect);
+                    while let Some(&(start, chunk)) =
+                        chunks.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        for_lines(chunk, |off, line| f(start + off, parse_line(&mut r, line)));
+                    }
+                });
+            }
+        });
+    }
+}
+
+fn parse_line<'a>(r: &mut Reader<'a>, line: &'a str) -> Result<Value<'a>> {
+    r.reset(line);
+    let v = tri!(Value::from_reader(r));
+    match tri!(r.skipnpeek()) {
+        None => Ok(v),
+        Some(_) => Err(r.err()),
+    }
+}
+
+// Split `input` into `(offset, chunk)` pairs of at least `size` bytes (except
+// the last), where each chunk ends just after a newline.
+fn split(input: &str, size: usize) -> Vec<(usize, &str)> {
+    let bs = input.as_bytes();
+    let mut out = Vec::with_capacity(bs.len() / size + 1);
+    let mut start = 0;
+    while start < bs.len() {
+        let min_end = (start + size).min(bs.len());
+        let end = (crate::scan::find_newline(bs, min_end) + 1).min(bs.len());
+        out.push((start, &input[start..end]));
+        start = end;
+    }
+    out
+}
+
+fn for_lines<'a>(chunk: &'a str, mut f: impl FnMut(usize, &'a str)) {
+    let mut off = 0;
+    for line in chunk.split_terminator('\n') {
+        if !line.trim().is_empty() {
+            f(off, line);
+        }
+        off += line.len() + 1;
+    }
+}
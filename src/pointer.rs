@@ -0,0 +1,208 @@
+//! Support for [JSON Pointers](https://tools.ietf.org/html/rfc6901), e.g.
+//! `/items/0/id`.
+//!
+//! [`Value::pointer`] looks a pointer up in an already-parsed value, and
+//! [`extract`] pulls the targets of several pointers directly out of a
+//! [`Reader`], without building the rest of the document.
+use crate::read::{Reader, Result, Token};
+use crate::value::Value;
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Split a JSON Pointer into its (unescaped) reference tokens.
+///
+/// Returns `None` if `ptr` isn't a valid JSON pointer — it must either be
+/// empty, or start with `/`, and `~` may only appear as `~0` or `~1`.
+pub fn parse(ptr: &str) -> Option<Vec<Cow<'_, str>>> {
+    if ptr.is_empty() {
+        return Some(vec![]);
+    }
+    let rest = ptr.strip_prefix('/')?;
+    let mut toks = vec![];
+    for t in rest.split('/') {
+        if !t.contains('~') {
+            toks.push(Cow::Borrowed(t));
+            continue;
+        }
+        let mut s = alloc::string::String::with_capacity(t.len());
+        let mut chars = t.chars();
+        while let Some(c) = chars.next() {
+            if c != '~' {
+                s.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('0') => s.push('~'),
+                Some('1') => s.push('/'),
+                _ => return None,
+            }
+        }
+        toks.push(Cow::Owned(s));
+    }
+    Some(toks)
+}
+
+/// Interpret a reference token as an array index. Per the RFC, leading zeros
+/// aren't allowed, and `-` (past the end of the array) never matches.
+pub(crate) fn array_index(tok: &str) -> Option<usize> {
+    let bs = tok.as_bytes();
+    if bs.is_empty() || (bs.len() > 1 && bs[0] == b'0') || !bs.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    tok.parse().ok()
+}
+
+pub(crate) fn lookup<'v, 'a, S: AsRef<str>>(
+    mut v: &'v Value<'a>,
+    toks: &[S],
+) -> Option<&'v Value<'a>> {
+    for t in toks {
+        let t = t.as_ref();
+        v = match v {
+            Value::Object(o) => o.get(t)?,
+            Value::Array(a) => a.get(array_index(t)?)?,
+            _ => return None,
+        };
+    }
+    Some(v)
+}
+
+/// Read the next value from `de`, returning only the values targeted by each
+/// of `pointers`, in the same order (or `None` for pointers which didn't
+/// resolve to anything, or which weren't valid pointers).
+///
+/// Anything which isn't on the path to one of the pointers is skipped with
+/// [`Reader::skip_value`], and reading stops as soon as all the pointers have
+/// been resolved. This means that on success, `de` may be left in the middle
+/// of the document, and the rest of it isn't checked for errors.
+///
+/// Like [`Value`], if an object has duplicate keys, the last one wins. So once
+/// a pointer has been resolved, the rest of every object it's inside still
+/// has to be read, and only arrays can be left early.
+///
+/// ```
+/// # use smoljson::{json, Reader, pointer};
+/// let mut r = Reader::new(r#"{"meta": {"version": 3}, "items": [{"id": "a"}, {"id": "b"}]}"#);
+/// let found = pointer::extract(&mut r, &["/meta/version", "/items/1/id", "/nope"]).unwrap();
+/// assert_eq!(found, vec![Some(json!(3)), Some(json!("b")), None]);
+/// ```
+pub fn extract<'a>(de: &mut Reader<'a>, pointers: &[&str]) -> Result<Vec<Option<Value<'a>>>> {
+    let parsed: Vec<Option<Vec<Cow<'_, str>>>> = pointers.iter().map(|p| parse(p)).collect();
+    let settled: Vec<bool> = parsed.iter().map(Option::is_none).collect();
+    let paths: Vec<Vec<Cow<'_, str>>> = parsed.into_iter().map(Option::unwrap_or_default).collect();
+    let active: Vec<usize> = (0..paths.len()).filter(|&i| !settled[i]).collect();
+    let mut ex = Extract {
+        remaining: active.len(),
+        paths: &paths,
+        out: vec![None; paths.len()],
+        settled,
+        open_objects: 0,
+    };
+    if ex.remaining != 0 {
+        tri!(ex.walk(de, &active, 0));
+    }
+    Ok(ex.out)
+}
+
+struct Extract<'p, 'a> {
+    paths: &'p [Vec<Cow<'p, str>>],
+    out: Vec<Option<Value<'a>>>,
+    settled: Vec<bool>,
+    remaining: usize,
+    // How many of the objects we're inside are still being read. Until these
+    // are done, a duplicate key could replace something already settled.
+    open_objects: usize,
+}
+
+impl<'p, 'a> Extract<'p, 'a> {
+    fn settle(&mut self, i: usize, v: Option<Value<'a>>) {
+        if !self.settled[i] {
+            self.settled[i] = true;
+            self.remaining -= 1;
+            self.out[i] = v;
+        }
+    }
+
+    fn walk(&mut self, de: &mut Reader<'a>, active: &[usize], depth: usize) -> Result<()> {
+        if active.iter().any(|&i| self.paths[i].len() == depth) {
+            // At least one pointer wants this whole value, so we need to build
+            // it anyway. Resolve the rest from the result.
+            let v = tri!(Value::from_reader(de));
+            for &i in active {
+                let found = lookup(&v, &self.paths[i][depth..]).cloned();
+                self.settle(i, found);
+            }
+            return Ok(());
+        }
+        let mut sub = vec![];
+        match tri!(de.next()) {
+            Token::ArrayBegin if tri!(de.skipnpeek()) == Some(b']') => {
+                tri!(de.next());
+            }
+            Token::ArrayBegin => {
+                let mut idx = 0;
+                loop {
+                    sub.clear();
+                    sub.extend(active.iter().copied().filter(|&i| {
+                        !self.settled[i] && array_index(&self.paths[i][depth]) == Some(idx)
+                    }));
+                    tri!(self.walk_or_skip(de, &sub, depth));
+                    if self.remaining == 0 && self.open_objects == 0 {
+                        return Ok(());
+                    }
+                    if !tri!(de.comma_or_array_end()) {
+                        break;
+                    }
+                    idx += 1;
+                }
+            }
+            Token::ObjectBegin if tri!(de.skipnpeek()) == Some(b'}') => {
+                tri!(de.next());
+            }
+            Token::ObjectBegin => {
+                self.open_objects += 1;
+                loop {
+                    let k = tri!(de.key());
+                    tri!(de.colon());
+                    sub.clear();
+                    sub.extend(
+                        active
+                            .iter()
+                            .copied()
+                            .filter(|&i| self.paths[i][depth] == k),
+                    );
+                    // A duplicate key replaces whatever we found for it before.
+                    for &i in &sub {
+                        if self.settled[i] {
+                            self.settled[i] = false;
+                            self.remaining += 1;
+                        }
+                    }
+                    tri!(self.walk_or_skip(de, &sub, depth));
+                    if !tri!(de.comma_or_obj_end()) {
+                        break;
+                    }
+                }
+                self.open_objects -= 1;
+            }
+            Token::Colon | Token::Comma | Token::ArrayEnd | Token::ObjectEnd => {
+                return Err(de.err());
+            }
+            // Scalars have no children, so nothing below can match.
+            _ => {}
+        }
+        for &i in active {
+            self.settle(i, None);
+        }
+        Ok(())
+    }
+
+    fn walk_or_skip(&mut self, de: &mut Reader<'a>, sub: &[usize], depth: usize) -> Result<()> {
+        if sub.is_empty() {
+            de.skip_value()
+        } else {
+            self.walk(de, sub, depth + 1)
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // use crate::value::Num;
+use crate::scan;
+use crate::value::Value;
 use alloc::borrow::Cow;
-use alloc::boxed::Box;
 use alloc::string::String;
 /// First lifetime is for strings borrowed from the source.
 /// Second lifetime is for strings borrowed from the parser.
@@ -12,7 +13,7 @@ pub(crate) enum Token<'s> {
     NumI(i64),
     NumF(f64),
     StrBorrow(&'s str),
-    StrOwn(Box<str>),
+    StrOwn(String),
     Colon,
     Comma,
     ObjectBegin,
@@ -23,8 +24,7 @@ pub(crate) enum Token<'s> {
 
 /// A set of flags describing non-standard extensions to JSON.
 ///
-/// Currently, this is just comments, but trailing commas may be added in the
-/// future.
+/// Currently, this is comments and trailing commas.
 ///
 /// Note: turning one of these flags on should never cause documents which were
 /// valid with that flag off to become invalid.
@@ -40,8 +40,9 @@ pub struct Dialect {
     /// `Dialect::CJSON`'s), cannot be nested. E.g. `/* /* this */ */` is
     /// invalid.
     pub allow_comments: bool,
-    // /// Allow a single extra trailing comma in array and object literals.
-    // pub allow_trailing_comma: bool,
+    /// Allow a single extra trailing comma in array and object literals, e.g.
+    /// `[1, 2,]`. Note that `[,]` is still invalid.
+    pub allow_trailing_comma: bool,
 }
 
 impl Default for Dialect {
@@ -55,7 +56,7 @@ impl Dialect {
     /// Fully standard JSON (no extensions).
     pub const STRICT: Self = Self {
         allow_comments: false,
-        // allow_trailing_comma: false,
+        allow_trailing_comma: false,
     };
 
     /// "JSON with Comments", as understood by Visual Studio Code.
@@ -65,8 +66,8 @@ impl Dialect {
     ///
     /// See <https://github.com/microsoft/node-jsonc-parser> for more details.
     pub const CJSON: Self = Self {
-        allow_comments: false,
-        // allow_trailing_comma: false,
+        allow_comments: true,
+        allow_trailing_comma: false,
     };
 
     /// The default settings.
@@ -79,11 +80,11 @@ impl Dialect {
     ///
     /// - `features = ["default_allow_comments"]`: enables
     ///   [`Dialect::allow_comments`] by default.
-    // /// - `default_allow_trailing_comma`: enables
-    // ///   [`Dialect::allow_trailing_comma`] by default.
+    /// - `features = ["default_allow_trailing_comma"]`: enables
+    ///   [`Dialect::allow_trailing_comma`] by default.
     pub const DEFAULT: Self = Self {
         allow_comments: cfg!(feature = "default_allow_comments"),
-        // allow_trailing_comma: cfg!(feature = "default_allow_trailing_comma"),
+        allow_trailing_comma: cfg!(feature = "default_allow_trailing_comma"),
     };
 
     /// All extensions enabled.
@@ -101,18 +102,18 @@ impl Dialect {
     /// unexpectedly break)
     pub const LOOSE: Self = Self {
         allow_comments: true,
-        // allow_trailing_comma: true,
+        allow_trailing_comma: true,
     };
     #[inline]
     pub const fn comments(mut self, v: bool) -> Self {
         self.allow_comments = v;
         self
     }
-    // #[inline]
-    // pub const fn trailing_comma(mut self, v: bool) -> Self {
-    //     self.allow_trailing_comma = v;
-    //     self
-    // }
+    #[inline]
+    pub const fn trailing_comma(mut self, v: bool) -> Self {
+        self.allow_trailing_comma = v;
+        self
+    }
 }
 
 // impl<'p> Token<'p> {
@@ -202,6 +203,38 @@ impl<'a> Reader<'a> {
         }
     }
 
+    /// Start reading `input` from the beginning, keeping this reader's dialect
+    /// and any memory it has allocated for unescaping strings.
+    ///
+    /// This is useful for reading many small documents (for example, the
+    /// lines of an NDJSON file). See also [`ParseContext`](crate::ParseContext).
+    pub fn reset(&mut self, input: &'a str) {
+        self.input = input;
+        self.bytes = input.as_bytes();
+        self.pos = 0;
+        self.tok_start = 0;
+        self.stash = None;
+    }
+
+    pub(crate) fn with_buf(input: &'a str, dialect: Dialect, buf: String) -> Self {
+        Self {
+            buf,
+            ..Self::with_dialect(input, dialect)
+        }
+    }
+
+    pub(crate) fn into_buf(self) -> String {
+        self.buf
+    }
+
+    /// Give back the allocation from a `Token::StrOwn` which was only
+    /// needed temporarily, so the next escaped string can reuse it.
+    pub(crate) fn reclaim(&mut self, s: String) {
+        if s.capacity() > self.buf.capacity() {
+            self.buf = s;
+        }
+    }
+
     #[inline]
     pub fn dialect_mut(&mut self) -> &mut Dialect {
         &mut self.dialect
@@ -217,6 +250,23 @@ impl<'a> Reader<'a> {
         self.pos.min(self.bytes.len())
     }
 
+    /// Byte offset of the start of the most recently read token.
+    pub(crate) fn token_start(&self) -> usize {
+        self.tok_start
+    }
+
+    /// Source text of the last token returned.
+    pub(crate) fn token_text(&self) -> &'a str {
+        &self.input[self.tok_start..self.pos]
+    }
+
+    /// Move to byte offset `pos`, which must be on a token boundary.
+    pub(crate) fn seek(&mut self, pos: usize) {
+        debug_assert!(self.input.is_char_boundary(pos));
+        self.stash = None;
+        self.pos = pos;
+    }
+
     #[cold]
     pub(super) fn err(&mut self) -> Error {
         #[cfg(any(debug_assertions, feature = "better_errors"))]
@@ -248,6 +298,11 @@ impl<'a> Reader<'a> {
     /// Returns `Err` if there are any more non-whitespace/non-comment (if this
     /// reader's dialect allows comments) characters in the input.
     pub fn finish(mut self) -> Result<()> {
+        self.check_end()
+    }
+
+    // `finish`, for when we still need the reader afterwards.
+    pub(crate) fn check_end(&mut self) -> Result<()> {
         match self.next_token() {
             Ok(Some(_)) => Err(self.err()),
             Ok(None) => Ok(()),
@@ -334,34 +389,21 @@ impl<'a> Reader<'a> {
     }
 
     fn skip_line_comment(&mut self) {
-        let (mut p, bs) = (self.pos, self.bytes);
-        while p < bs.len() && bs[p] != b'\n' {
-            p += 1;
-        }
-        self.pos = p;
+        self.pos = scan::find_newline(self.bytes, self.pos);
     }
 
     fn skip_block_comment(&mut self) -> Result<()> {
-        let (mut p, bs) = (self.pos, self.bytes);
-        loop {
-            if p + 1 >= bs.len() {
-                self.pos = p;
-                return Err(self.err());
-            }
-            if bs[p] == b'*' && bs[p + 1] == b'/' {
-                self.pos = p + 2;
-                return Ok(());
-            }
-            p += 1;
+        let p = scan::find_block_end(self.bytes, self.pos);
+        if p + 1 >= self.bytes.len() {
+            self.pos = p;
+            return Err(self.err());
         }
+        self.pos = p + 2;
+        Ok(())
     }
 
     fn skip_ws_only(&mut self) {
-        let (mut p, bs) = (self.pos, self.bytes);
-        while p < bs.len() && matches!(bs[p], b'\n' | b' ' | b'\t' | b'\r') {
-            p += 1;
-        }
-        self.pos = p;
+        self.pos = scan::skip_ws(self.bytes, self.pos);
     }
 
     fn cur_ch(&self) -> Option<char> {
@@ -518,8 +560,8 @@ impl<'a> Reader<'a> {
         let text = &self.input[self.tok_start..self.pos];
         if !float {
             if neg {
-                if let Ok(i) = text.parse::<i64>() {
-                    debug_assert!(i < 0);
+                // `-0` isn't negative, so it's left for the float case.
+                if let Ok(i @ i64::MIN..=-1) = text.parse::<i64>() {
                     return Ok(Token::NumI(i));
                 }
             } else if let Ok(u) = text.parse::<u64>() {
@@ -537,11 +579,8 @@ impl<'a> Reader<'a> {
         self.buf.clear();
         let bs = self.bytes;
         loop {
-            let mut p = self.pos;
-            let start = p;
-            while p < bs.len() && bs[p] != b'"' && bs[p] != b'\\' {
-                p += 1;
-            }
+            let start = self.pos;
+            let p = scan::find_quote_or_escape(bs, start);
             if p == bs.len() || !self.input.is_char_boundary(p) {
                 self.pos = p;
                 return Err(self.err());
@@ -553,7 +592,10 @@ impl<'a> Reader<'a> {
             }
             self.buf.push_str(&self.input[start..p]);
             if bs[p] == b'"' {
-                return Ok(Token::StrOwn(self.buf.clone().into_boxed_str()));
+                // hand over the buffer itself rather than copying it. Callers
+                // which only needed to look at it can give it back with
+                // `reclaim`.
+                return Ok(Token::StrOwn(core::mem::take(&mut self.buf)));
             }
             debug_assert_eq!(bs[p], b'\\');
             tri!(self.unescape_next());
@@ -591,7 +633,7 @@ impl<'a> Reader<'a> {
     }
     pub(crate) fn comma_or_obj_end(&mut self) -> Result<bool> {
         match self.next_token() {
-            Ok(Some(Token::Comma)) => Ok(true),
+            Ok(Some(Token::Comma)) => self.more_after_comma(b'}'),
             Ok(Some(Token::ObjectEnd)) => Ok(false),
             Err(e) => Err(e),
             _ => Err(self.err()),
@@ -599,21 +641,119 @@ impl<'a> Reader<'a> {
     }
     pub(crate) fn comma_or_array_end(&mut self) -> Result<bool> {
         match self.next_token() {
-            Ok(Some(Token::Comma)) => Ok(true),
+            Ok(Some(Token::Comma)) => self.more_after_comma(b']'),
             Ok(Some(Token::ArrayEnd)) => Ok(false),
             Err(e) => Err(e),
             _ => Err(self.err()),
         }
     }
+    // Returns false (after reading `close`) if the comma we just read was a
+    // trailing comma the dialect allows.
+    fn more_after_comma(&mut self, close: u8) -> Result<bool> {
+        if self.dialect.allow_trailing_comma && tri!(self.skipnpeek()) == Some(close) {
+            self.bump();
+            return Ok(false);
+        }
+        Ok(true)
+    }
     pub(crate) fn key(&mut self) -> Result<Cow<'a, str>> {
         match self.next_token() {
             Ok(Some(Token::StrBorrow(b))) => Ok(Cow::Borrowed(b)),
-            Ok(Some(Token::StrOwn(b))) => Ok(Cow::Owned(b.into())),
+            Ok(Some(Token::StrOwn(b))) => Ok(Cow::Owned(b)),
             Err(e) => Err(e),
-            Ok(Some(_t)) => {
+            _ => Err(self.err()),
+        }
+    }
+
+    /// Skip over the next value (including any nested arrays and objects)
+    /// without building anything.
+    ///
+    /// Strings are scanned without being unescaped, and numbers are checked
+    /// against the JSON grammar without being converted, so this is
+    /// considerably cheaper than reading a value and discarding it.
+    pub fn skip_value(&mut self) -> Result<()> {
+        if let Some(t) = self.stash.take() {
+            return match t {
+                Token::ArrayBegin => self.skip_array_rest(),
+                Token::ObjectBegin => self.skip_obj_rest(),
+                Token::Colon | Token::Comma | Token::ArrayEnd | Token::ObjectEnd => Err(self.err()),
+                _ => Ok(()),
+            };
+        }
+        tri!(self.skip_trivial());
+        self.tok_start = self.pos;
+        match tri!(self.bnext_or_err()) {
+            b'"' => self.skip_string(),
+            b't' => self.read_keyword(b"true", Token::Null).map(drop),
+            b'f' => self.read_keyword(b"false", Token::Null).map(drop),
+            b'n' => self.read_keyword(b"null", Token::Null).map(drop),
+            b'-' | b'0'..=b'9' => match crate::lex::number_end(self.bytes, self.tok_start) {
+                Some(end) => {
+                    self.pos = end;
+                    match self.bpeek() {
+                        Some(b) if !self.is_delim_byte(b) => Err(self.err()),
+                        _ => Ok(()),
+                    }
+                }
+                None => Err(self.err()),
+            },
+            b'[' => self.skip_array_rest(),
+            b'{' => self.skip_obj_rest(),
+            _ => Err(self.err()),
+        }
+    }
+
+    fn skip_array_rest(&mut self) -> Result<()> {
+        if tri!(self.skipnpeek()) == Some(b']') {
+            self.bump();
+            return Ok(());
+        }
+        loop {
+            tri!(self.skip_value());
+            if !tri!(self.comma_or_array_end()) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_obj_rest(&mut self) -> Result<()> {
+        if tri!(self.skipnpeek()) == Some(b'}') {
+            self.bump();
+            return Ok(());
+        }
+        loop {
+            if tri!(self.skipnpeek()) != Some(b'"') {
+                return Err(self.err());
+            }
+            self.bump();
+            tri!(self.skip_string());
+            tri!(self.colon());
+            tri!(self.skip_value());
+            if !tri!(self.comma_or_obj_end()) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_string(&mut self) -> Result<()> {
+        let bs = self.bytes;
+        loop {
+            let p = scan::find_quote_or_escape(bs, self.pos);
+            if p == bs.len() {
+                self.pos = p;
                 return Err(self.err());
             }
-            _o => return Err(self.err()),
+            self.pos = p + 1;
+            if bs[p] == b'"' {
+                return Ok(());
+            }
+            match tri!(self.bnext_or_err()) {
+                b'b' | b'f' | b'n' | b'r' | b't' | b'\\' | b'/' | b'"' => {}
+                b'u' => {
+                    tri!(self.single_hex_escape());
+                }
+                _ => return Err(self.err()),
+            }
         }
     }
 }
@@ -627,19 +767,6 @@ fn dec_utf16_single(a: u16, b: u16) -> Option<char> {
     core::char::from_u32(c)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    #[test]
-    fn test_u16() {
-        for c in (0x10000..0x110000).filter_map(core::char::from_u32) {
-            let mut buf = [0, 0];
-            c.encode_utf16(&mut buf);
-            assert_eq!(dec_utf16_single(buf[0], buf[1]), Some(c));
-        }
-    }
-}
-
 impl<'a> Reader<'a> {
     // pub fn peek_bool(&mut self) -> Result<bool> {
     //     if let Ok(Some(Token::Bool(b))) = self.peek_token() {
@@ -678,12 +805,177 @@ impl<'a> Reader<'a> {
     pub fn read_str(&mut self) -> Result<Cow<'a, str>> {
         match self.next_token() {
             Ok(Some(Token::StrBorrow(s))) => Ok(Cow::Borrowed(s)),
-            Ok(Some(Token::StrOwn(s))) => Ok(Cow::Owned(s.into())),
+            Ok(Some(Token::StrOwn(s))) => Ok(Cow::Owned(s)),
             Err(e) => Err(e),
             _ => Err(self.err()),
         }
     }
     // pub fn read_object(&mut self) -> Result<()> {}
+
+    /// Returns an iterator over the elements of the array at the current
+    /// position, parsing each one into a [`Value`] only when it's requested.
+    ///
+    /// The opening `[` is consumed on the first call to `next`, and the
+    /// closing `]` after the last element. This allows processing arrays far
+    /// larger than would comfortably fit in memory as a single `Value`, since
+    /// each element may be dropped before the next is parsed.
+    ///
+    /// After an error, the iterator returns `None` forever.
+    ///
+    /// ```
+    /// # use smoljson::Reader;
+    /// let mut r = Reader::new(r#"[{"id": 1}, {"id": 2}]"#);
+    /// let mut sum = 0;
+    /// for item in r.array_items() {
+    ///     sum += item.unwrap()["id"].as_u64().unwrap();
+    /// }
+    /// assert_eq!(sum, 3);
+    /// r.finish().unwrap();
+    /// ```
+    pub fn array_items(&mut self) -> ArrayItems<'_, 'a, fn(&mut Reader<'a>) -> Result<Value<'a>>> {
+        self.array_items_with(Value::from_reader)
+    }
+
+    /// Like [`Reader::array_items`], but each element is read with `f`, which
+    /// should consume exactly one value (e.g. `Reader::read_u64`).
+    pub fn array_items_with<T, F>(&mut self, f: F) -> ArrayItems<'_, 'a, F>
+    where
+        F: FnMut(&mut Reader<'a>) -> Result<T>,
+    {
+        ArrayItems {
+            de: self,
+            read: f,
+            state: ItemsState::Start,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ItemsState {
+    Start,
+    First,
+    Rest,
+    Done,
+}
+
+/// Iterator over the elements of a JSON array. See [`Reader::array_items`].
+pub struct ArrayItems<'r, 'a, F> {
+    de: &'r mut Reader<'a>,
+    read: F,
+    state: ItemsState,
+}
+
+impl<'r, 'a, T, F> ArrayItems<'r, 'a, F>
+where
+    F: FnMut(&mut Reader<'a>) -> Result<T>,
+{
+    fn step(&mut self) -> Result<Option<T>> {
+        match self.state {
+            ItemsState::Done => return Ok(None),
+            ItemsState::Start => {
+                tri!(self.de.array_begin());
+                self.state = ItemsState::First;
+                return self.step();
+            }
+            ItemsState::First => {
+                if tri!(self.de.skipnpeek()) == Some(b']') {
+                    tri!(self.de.next_token());
+                    return Ok(None);
+                }
+            }
+            ItemsState::Rest => {
+                if !tri!(self.de.comma_or_array_end()) {
+                    return Ok(None);
+                }
+            }
+        }
+        self.state = ItemsState::Rest;
+        (self.read)(self.de).map(Some)
+    }
+}
+
+impl<'r, 'a, T, F> Iterator for ArrayItems<'r, 'a, F>
+where
+    F: FnMut(&mut Reader<'a>) -> Result<T>,
+{
+    type Item = Result<T>;
+    fn next(&mut self) -> Option<Result<T>> {
+        match self.step() {
+            Ok(Some(v)) => Some(Ok(v)),
+            Ok(None) => {
+                self.state = ItemsState::Done;
+                None
+            }
+            Err(e) => {
+                self.state = ItemsState::Done;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'r, 'a, T, F> core::iter::FusedIterator for ArrayItems<'r, 'a, F> where
+    F: FnMut(&mut Reader<'a>) -> Result<T>
+{
+}
+
+/// Remove the comments from JSON with comments (see [`Dialect::CJSON`]), so
+/// that it can be given to something which only accepts strict JSON.
+///
+/// Each comment is replaced with a single space, so that the tokens on either
+/// side of it stay separate. If `replace_with_spaces` is true, each byte of it
+/// is replaced with a space instead (except line breaks, which are kept), so
+/// byte offsets and line numbers in the result still match the input.
+///
+/// Trailing commas are removed too if the [default `Dialect`](Dialect::DEFAULT)
+/// allows them. Use [`strip_comments_with`] to choose the dialect.
+///
+/// ```
+/// # use smoljson::strip_comments;
+/// let src = "{\"a\": 1 /* one */, // end\n\"b\": \"//\"}";
+/// assert_eq!(strip_comments(src, false), "{\"a\": 1  ,  \n\"b\": \"//\"}");
+/// assert_eq!(strip_comments(src, true).len(), src.len());
+/// ```
+pub fn strip_comments(input: &str, replace_with_spaces: bool) -> String {
+    strip_comments_with(input, Dialect::DEFAULT, replace_with_spaces)
+}
+
+/// Like [`strip_comments`], but also removing trailing commas if `dialect`
+/// allows them. Comments are removed regardless of `dialect`.
+///
+/// ```
+/// # use smoljson::{strip_comments_with, Dialect};
+/// let src = "[1, 2, // two\n]";
+/// assert_eq!(strip_comments_with(src, Dialect::LOOSE, false), "[1, 2  \n]");
+/// assert_eq!(strip_comments_with(src, Dialect::STRICT, false), "[1, 2,  \n]");
+/// ```
+pub fn strip_comments_with(input: &str, dialect: Dialect, replace_with_spaces: bool) -> String {
+    use crate::lex::{Kind, Lexer};
+    let mut out = String::with_capacity(input.len());
+    let mut lexer = Lexer::new(input, dialect.comments(true));
+    while let Some(t) = lexer.next() {
+        let text = &input[t.span];
+        let comment = matches!(t.kind, Kind::LineComment | Kind::BlockComment);
+        let strip = comment
+            || (t.kind == Kind::Comma
+                && dialect.allow_trailing_comma
+                && matches!(
+                    lexer.clone().find(|t| !t.kind.is_trivia()),
+                    Some(t) if matches!(t.kind, Kind::ObjectEnd | Kind::ArrayEnd)
+                ));
+        if !strip {
+            out.push_str(text);
+        } else if replace_with_spaces {
+            out.extend(text.bytes().map(|b| match b {
+                b'\n' | b'\r' => b as char,
+                _ => ' ',
+            }));
+        } else if comment {
+            // Otherwise `1/**/2` would turn into `12`.
+            out.push(' ');
+        }
+    }
+    out
 }
 
 // pub trait ReadJson {
@@ -774,3 +1066,16 @@ impl<T: WriteJson> WriteJson for Option<T> {
     }
 }
 */
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_u16() {
+        for c in (0x10000..0x110000).filter_map(core::char::from_u32) {
+            let mut buf = [0, 0];
+            c.encode_utf16(&mut buf);
+            assert_eq!(dec_utf16_single(buf[0], buf[1]), Some(c));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Word-at-a-time (SWAR) byte scanning for the lexer's hot loops.
+//!
+//! Each function returns the index of the first matching byte at or after
+//! `p`, or `bs.len()` if there is none. They're only public (with the `bench`
+//! feature) so that the benchmarks can get at them.
+//!
+//! The trick is `eq_mask`, which tests all 8 bytes of a `u64` for equality at
+//! once without carries between lanes (the classic `haszero` bithack can
+//! report false positives above the first match, which would be wrong for the
+//! negated masks used by `skip_ws`).
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+#[inline(always)]
+fn load(bs: &[u8], p: usize) -> u64 {
+    let mut b = [0u8; 8];
+    b.copy_from_slice(&bs[p..p + 8]);
+    u64::from_le_bytes(b)
+}
+
+/// High bit of each byte of the result is set iff that byte of `w` is `b`.
+#[inline(always)]
+fn eq_mask(w: u64, b: u8) -> u64 {
+    let x = w ^ (LO * b as u64);
+    !(((x & !HI) + !HI) | x) & HI
+}
+
+#[inline(always)]
+fn find_by(bs: &[u8], mut p: usize, mask: impl Fn(u64) -> u64, hit: impl Fn(u8) -> bool) -> usize {
+    while p + 8 <= bs.len() {
+        let m = mask(load(bs, p));
+        if m != 0 {
+            return p + (m.trailing_zeros() / 8) as usize;
+        }
+        p += 8;
+    }
+    while p < bs.len() && !hit(bs[p]) {
+        p += 1;
+    }
+    p
+}
+
+#[inline(always)]
+fn is_ws(b: u8) -> bool {
+    matches!(b, b'\n' | b' ' | b'\t' | b'\r')
+}
+
+/// First byte which isn't JSON whitespace.
+#[inline]
+pub fn skip_ws(bs: &[u8], p: usize) -> usize {
+    // Most runs of whitespace are empty or a single space (in minified and
+    // pretty-printed input respectively), so check those before going wide.
+    match bs.get(p) {
+        Some(&b) if !is_ws(b) => return p,
+        None => return p,
+        _ => {}
+    }
+    match bs.get(p + 1) {
+        Some(&b) if !is_ws(b) => return p + 1,
+        None => return p + 1,
+        _ => {}
+    }
+    find_by(
+        bs,
+        p + 2,
+        |w| !(eq_mask(w, b' ') | eq_mask(w, b'\n') | eq_mask(w, b'\t') | eq_mask(w, b'\r')) & HI,
+        |b| !is_ws(b),
+    )
+}
+
+/// First `"` or `\`.
+#[inline]
+pub fn find_quote_or_escape(bs: &[u8], mut p: usize) -> usize {
+    // Keys and short strings are very common, so don't go wide right away.
+    for _ in 0..4 {
+        match bs.get(p) {
+            Some(&b) if b != b'"' && b != b'\\' => p += 1,
+            _ => return p,
+        }
+    }
+    find_by(
+        bs,
+        p,
+        |w| eq_mask(w, b'"') | eq_mask(w, b'\\'),
+        |b| b == b'"' || b == b'\\',
+    )
+}
+
+/// First `\n`.
+#[inline]
+pub fn find_newline(bs: &[u8], p: usize) -> usize {
+    find_by(bs, p, |w| eq_mask(w, b'\n'), |b| b == b'\n')
+}
+
+/// Index of the `*` of the first `*/`.
+#[inline]
+pub fn find_block_end(bs: &[u8], mut p: usize) -> usize {
+    loop {
+        p = find_by(bs, p, |w| eq_mask(w, b'*'), |b| b == b'*');
+        if p + 1 >= bs.len() || bs[p + 1] == b'/' {
+            return p.min(bs.len());
+        }
+        p += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_against_bytewise() {
+        // All the bytes we care about, plus their neighbors (which would trip
+        // up a carry-based zero test).
+        let alphabet = b" !\t\n\x0b\r\x0e\"#*+/0[\\]\x80\xff";
+        let mut state = 1u32;
+        for len in 0..40 {
+            for _ in 0..200 {
+                let bs: alloc::vec::Vec<u8> = (0..len)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 17;
+                        state ^= state << 5;
+                        alphabet[state as usize % alphabet.len()]
+                    })
+                    .collect();
+                for p in 0..=bs.len() {
+                    let naive = |f: &dyn Fn(u8) -> bool| {
+                        (p..bs.len()).find(|&i| f(bs[i])).unwrap_or(bs.len())
+                    };
+                    assert_eq!(skip_ws(&bs, p), naive(&|b| !is_ws(b)), "{:?} {}", bs, p);
+                    assert_eq!(
+                        find_quote_or_escape(&bs, p),
+                        naive(&|b| b == b'"' || b == b'\\')
+                    );
+                    assert_eq!(find_newline(&bs, p), naive(&|b| b == b'\n'));
+                    let block = (p..bs.len().saturating_sub(1))
+                        .find(|&i| bs[i] == b'*' && bs[i + 1] == b'/');
+                    let got = find_block_end(&bs, p);
+                    match block {
+                        Some(i) => assert_eq!(got, i),
+                        None => assert!(got + 1 >= bs.len()),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! A parse tree which remembers where everything came from.
+//!
+//! Every node of a [`SpannedValue`], and every object key, carries the byte
+//! range it occupied in the source, which makes it possible to report
+//! problems found after parsing (e.g. during validation) at the right spot.
+//!
+//! ```
+//! # use smoljson::spanned::SpannedValue;
+//! let src = r#"{"port": 70000}"#;
+//! let v = SpannedValue::from_str(src).unwrap();
+//! let port = v.pointer("/port").unwrap();
+//! assert_eq!(&src[port.span.clone()], "70000");
+//! assert_eq!(v.pointer_span("/port"), Some(9..14));
+//! ```
+use crate::pointer;
+use crate::read::{Dialect, Reader, Result, Token};
+use crate::value::{Num, Value};
+use crate::write::KeyOrder;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedValue<'a> {
+    /// Byte range of this value, including the quotes for strings, and the
+    /// brackets for arrays and objects.
+    pub span: Range<usize>,
+    pub kind: Kind<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind<'a> {
+    Null,
+    Bool(bool),
+    Num(Num),
+    Str(Cow<'a, str>),
+    Array(Vec<SpannedValue<'a>>),
+    /// Entries are in the order they appear in the source (and duplicate keys
+    /// are kept).
+    Object(Vec<Entry<'a>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry<'a> {
+    pub key: Cow<'a, str>,
+    /// Byte range of the key, including quotes.
+    pub key_span: Range<usize>,
+    pub value: SpannedValue<'a>,
+}
+
+impl<'a> SpannedValue<'a> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'a str) -> Result<Self> {
+        Self::from_str_with(s, Dialect::DEFAULT)
+    }
+
+    pub fn from_str_with(input: &'a str, d: Dialect) -> Result<Self> {
+        let mut de = Reader::with_dialect(input, d);
+        let r = tri!(Self::from_reader(&mut de));
+        tri!(de.finish());
+        Ok(r)
+    }
+
+    pub fn from_reader(de: &mut Reader<'a>) -> Result<Self> {
+        let t = tri!(de.next());
+        let start = de.token_start();
+        let kind = match t {
+            Token::Null => Kind::Null,
+            Token::Bool(b) => Kind::Bool(b),
+            Token::NumF(n) => Kind::Num(Num::from(n)),
+            Token::NumI(n) => Kind::Num(Num::from(n)),
+            Token::NumU(n) => Kind::Num(Num::from(n)),
+            Token::StrBorrow(b) => Kind::Str(Cow::Borrowed(b)),
+            Token::StrOwn(b) => Kind::Str(Cow::Owned(b)),
+            Token::ArrayBegin => tri!(Self::read_array(de)),
+            Token::ObjectBegin => tri!(Self::read_obj(de)),
+            _ => return Err(de.err()),
+        };
+        Ok(Self {
+            span: start..de.position(),
+            kind,
+        })
+    }
+
+    fn read_array(de: &mut Reader<'a>) -> Result<Kind<'a>> {
+        let mut v = Vec::new();
+        if tri!(de.skipnpeek()) == Some(b']') {
+            tri!(de.next());
+            return Ok(Kind::Array(v));
+        }
+        loop {
+            v.push(tri!(Self::from_reader(de)));
+            if !tri!(de.comma_or_array_end()) {
+                return Ok(Kind::Array(v));
+            }
+        }
+    }
+
+    fn read_obj(de: &mut Reader<'a>) -> Result<Kind<'a>> {
+        let mut v = Vec::new();
+        if tri!(de.skipnpeek()) == Some(b'}') {
+            tri!(de.next());
+            return Ok(Kind::Object(v));
+        }
+        loop {
+            let key = tri!(de.key());
+            let key_span = de.token_start()..de.position();
+            tri!(de.colon());
+            let value = tri!(Self::from_reader(de));
+            v.push(Entry {
+                key,
+                key_span,
+                value,
+            });
+            if !tri!(de.comma_or_obj_end()) {
+                return Ok(Kind::Object(v));
+            }
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[SpannedValue<'a>]> {
+        opt_extract!(&self.kind, Kind::Array(a) => Some(&a[..]))
+    }
+
+    pub fn as_object(&self) -> Option<&[Entry<'a>]> {
+        opt_extract!(&self.kind, Kind::Object(o) => Some(&o[..]))
+    }
+
+    /// The entry for `key`, if this is an object. As with [`Value`], if a key
+    /// is present more than once, the last one wins.
+    pub fn entry(&self, key: &str) -> Option<&Entry<'a>> {
+        self.as_object()?.iter().rev().find(|e| e.key == key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SpannedValue<'a>> {
+        self.entry(key).map(|e| &e.value)
+    }
+
+    pub fn at(&self, i: usize) -> Option<&SpannedValue<'a>> {
+        self.as_array()?.get(i)
+    }
+
+    /// Look up a value by [JSON Pointer](https://tools.ietf.org/html/rfc6901).
+    pub fn pointer(&self, ptr: &str) -> Option<&SpannedValue<'a>> {
+        let mut v = self;
+        for t in pointer::parse(ptr)? {
+            v = match &v.kind {
+                Kind::Object(_) => v.get(&t)?,
+                Kind::Array(a) => a.get(pointer::array_index(&t)?)?,
+                _ => return None,
+            };
+        }
+        Some(v)
+    }
+
+    /// The span of the value a JSON Pointer refers to.
+    pub fn pointer_span(&self, ptr: &str) -> Option<Range<usize>> {
+        self.pointer(ptr).map(|v| v.span.clone())
+    }
+
+    /// Sort the entries of every object in this tree by `order`, which is
+    /// stable (duplicate keys keep their relative order). Spans are left
+    /// alone, so they still point into the source.
+    ///
+    /// ```
+    /// # use smoljson::{spanned::SpannedValue, write::KeyOrder};
+    /// let mut v = SpannedValue::from_str(r#"{"b": {"y": 1, "x": 2}, "a": 3}"#).unwrap();
+    /// v.sort_keys(&KeyOrder::Lexicographic);
+    /// let keys = v.as_object().unwrap().iter().map(|e| &*e.key).collect::<Vec<_>>();
+    /// assert_eq!(keys, ["a", "b"]);
+    /// assert_eq!(v.get("b").unwrap().as_object().unwrap()[0].key, "x");
+    /// ```
+    pub fn sort_keys(&mut self, order: &KeyOrder) {
+        match &mut self.kind {
+            Kind::Array(a) => a.iter_mut().for_each(|v| v.sort_keys(order)),
+            Kind::Object(o) => {
+                o.sort_by(|a, b| order.compare(&a.key, &b.key));
+                o.iter_mut().for_each(|e| e.value.sort_keys(order));
+            }
+            _ => {}
+        }
+    }
+
+    /// Convert to a plain [`Value`], discarding the spans.
+    pub fn into_value(self) -> Value<'a> {
+        match self.kind {
+            Kind::Null => Value::Null,
+            Kind::Bool(b) => Value::Bool(b),
+            Kind::Num(n) => Value::Num(n),
+            Kind::Str(s) => Value::Str(s),
+            Kind::Array(a) => a.into_iter().map(Self::into_value).collect(),
+            Kind::Object(o) => o
+                .into_iter()
+                .map(|e| (e.key, e.value.into_value()))
+                .collect(),
+        }
+    }
+
+    /// Like [`SpannedValue::into_value`], but borrowing.
+    pub fn to_value(&self) -> Value<'_> {
+        match &self.kind {
+            Kind::Null => Value::Null,
+            Kind::Bool(b) => Value::Bool(*b),
+            Kind::Num(n) => Value::Num(*n),
+            Kind::Str(s) => Value::Str(Cow::Borrowed(s)),
+            Kind::Array(a) => a.iter().map(Self::to_value).collect(),
+            Kind::Object(o) => o.iter().map(|e| (&*e.key, e.value.to_value())).collect(),
+        }
+    }
+}
+
+impl<'a> From<SpannedValue<'a>> for Value<'a> {
+    fn from(v: SpannedValue<'a>) -> Self {
+        v.into_value()
+    }
+}
@@ -0,0 +1,416 @@
+//! A compact, read-only document representation.
+//!
+//! [`Document`] stores the whole parse result in one flat array (the "tape"),
+//! in the style of simdjson. Strings which don't need unescaping point into
+//! the source, and the rest share a single buffer, so parsing does a handful
+//! of allocations total, rather than one per array, object and string as with
+//! [`Value`].
+//!
+//! ```
+//! # use smoljson::{json, tape::Document};
+//! let doc = Document::from_str(r#"{"name": "x", "ports": [80, 443]}"#).unwrap();
+//! let root = doc.root();
+//! assert_eq!(root.get("name").and_then(|n| n.as_str()), Some("x"));
+//! assert_eq!(root.get("ports").and_then(|p| p.at(1)).and_then(|p| p.as_u64()), Some(443));
+//! assert_eq!(doc.to_value(), json!({"name": "x", "ports": [80, 443]}));
+//! ```
+use crate::read::{Dialect, Reader, Result, Token};
+use crate::value::{Num, Value};
+use crate::write::{self, WriteJson, Writer};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Null,
+    Bool(bool),
+    Num(Num),
+    // Range in the source text.
+    Src(usize, usize),
+    // Range in `Document::strs`.
+    Buf(usize, usize),
+    // `end` is the index of the first slot after the container's contents,
+    // `len` the number of elements (or key/value pairs). Object contents are
+    // stored as alternating key (a string slot) and value.
+    Array { end: usize, len: usize },
+    Object { end: usize, len: usize },
+}
+
+// Memory only needed during a parse, which an `Arena` holds on to between
+// parses: the stack of open containers, and the `Reader`'s string buffer.
+#[derive(Debug, Default)]
+pub(crate) struct Scratch {
+    stack: Vec<usize>,
+    buf: String,
+}
+
+impl Scratch {
+    pub(crate) const fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            buf: String::new(),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.stack.capacity() * core::mem::size_of::<usize>() + self.buf.capacity()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Document<'a> {
+    src: &'a str,
+    tape: Vec<Slot>,
+    strs: String,
+}
+
+impl<'a> Document<'a> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'a str) -> Result<Self> {
+        Self::from_str_with(s, Dialect::DEFAULT)
+    }
+
+    pub fn from_str_with(src: &'a str, d: Dialect) -> Result<Self> {
+        let mut doc = Document {
+            src,
+            tape: Vec::with_capacity(src.len() / 8),
+            strs: String::new(),
+        };
+        tri!(doc.read(d, &mut Scratch::new()));
+        Ok(doc)
+    }
+
+    // Holds no buffers, and has no root, so it mustn't be handed out as is.
+    pub(crate) const fn empty() -> Document<'static> {
+        Document {
+            src: "",
+            tape: Vec::new(),
+            strs: String::new(),
+        }
+    }
+
+    /// A document for `src` (which still needs to be [read](Self::read))
+    /// which takes over `self`'s buffers, leaving it empty.
+    pub(crate) fn with_src<'b>(&mut self, src: &'b str) -> Document<'b> {
+        Document {
+            src,
+            tape: core::mem::take(&mut self.tape),
+            strs: core::mem::take(&mut self.strs),
+        }
+    }
+
+    // Parse `self.src`, replacing anything already in the buffers.
+    pub(crate) fn read(&mut self, d: Dialect, scratch: &mut Scratch) -> Result<()> {
+        self.tape.clear();
+        self.strs.clear();
+        scratch.stack.clear();
+        let buf = core::mem::take(&mut scratch.buf);
+        let mut de = Reader::with_buf(self.src, d, buf);
+        let res = self
+            .parse(&mut de, &mut scratch.stack)
+            .and_then(|_| de.check_end());
+        scratch.buf = de.into_buf();
+        res
+    }
+
+    /// Bytes reserved by the document's buffers.
+    pub(crate) fn capacity(&self) -> usize {
+        self.tape.capacity() * core::mem::size_of::<Slot>() + self.strs.capacity()
+    }
+
+    fn str_slot(&mut self, de: &mut Reader<'a>, t: Token<'a>) -> Option<Slot> {
+        match t {
+            Token::StrBorrow(s) => {
+                let start = s.as_ptr() as usize - self.src.as_ptr() as usize;
+                Some(Slot::Src(start, start + s.len()))
+            }
+            Token::StrOwn(s) => {
+                let start = self.strs.len();
+                self.strs.push_str(&s);
+                de.reclaim(s);
+                Some(Slot::Buf(start, self.strs.len()))
+            }
+            _ => None,
+        }
+    }
+
+    // `stack` gets the indices of the containers we're currently inside.
+    fn parse(&mut self, de: &mut Reader<'a>, stack: &mut Vec<usize>) -> Result<()> {
+        loop {
+            let slot = match tri!(de.next()) {
+                Token::Null => Slot::Null,
+                Token::Bool(b) => Slot::Bool(b),
+                Token::NumF(n) => Slot::Num(Num::from(n)),
+                Token::NumI(n) => Slot::Num(Num::from(n)),
+                Token::NumU(n) => Slot::Num(Num::from(n)),
+                Token::ArrayBegin => Slot::Array { end: 0, len: 0 },
+                Token::ObjectBegin => Slot::Object { end: 0, len: 0 },
+                t => match self.str_slot(de, t) {
+                    Some(s) => s,
+                    None => return Err(de.err()),
+                },
+            };
+            self.tape.push(slot);
+            let opened = matches!(slot, Slot::Array { .. } | Slot::Object { .. });
+            let mut just_opened = opened;
+            if opened {
+                stack.push(self.tape.len() - 1);
+            }
+            // Close as many containers as we can, and then read up to the
+            // start of the next value.
+            loop {
+                let top = match stack.last() {
+                    Some(&t) => t,
+                    None => return Ok(()),
+                };
+                let is_obj = matches!(self.tape[top], Slot::Object { .. });
+                let more = if just_opened {
+                    let close = if is_obj { b'}' } else { b']' };
+                    if tri!(de.skipnpeek()) == Some(close) {
+                        tri!(de.next());
+                        false
+                    } else {
+                        true
+                    }
+                } else if is_obj {
+                    tri!(de.comma_or_obj_end())
+                } else {
+                    tri!(de.comma_or_array_end())
+                };
+                just_opened = false;
+                let end = self.tape.len();
+                match &mut self.tape[top] {
+                    Slot::Array { len, end: e } | Slot::Object { len, end: e } => {
+                        if more {
+                            *len += 1;
+                        } else {
+                            *e = end;
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+                if more {
+                    if is_obj {
+                        let k = tri!(de.next());
+                        match self.str_slot(de, k) {
+                            Some(s) => self.tape.push(s),
+                            None => return Err(de.err()),
+                        }
+                        tri!(de.colon());
+                    }
+                    break;
+                }
+                stack.pop();
+            }
+        }
+    }
+
+    /// The top-level value.
+    pub fn root(&self) -> Node<'_, 'a> {
+        Node { doc: self, idx: 0 }
+    }
+
+    /// Convert the whole document into a [`Value`]. Strings are borrowed
+    /// from the document where possible.
+    pub fn to_value(&self) -> Value<'_> {
+        self.root().to_value()
+    }
+
+    fn str_at(&self, idx: usize) -> Option<&str> {
+        match self.tape[idx] {
+            Slot::Src(s, e) => Some(&self.src[s..e]),
+            Slot::Buf(s, e) => Some(&self.strs[s..e]),
+            _ => None,
+        }
+    }
+
+    // Index of the slot after the value starting at `idx`.
+    fn skip(&self, idx: usize) -> usize {
+        match self.tape[idx] {
+            Slot::Array { end, .. } | Slot::Object { end, .. } => end,
+            _ => idx + 1,
+        }
+    }
+}
+
+/// A cursor to a value inside a [`Document`].
+#[derive(Clone, Copy)]
+pub struct Node<'d, 'a> {
+    doc: &'d Document<'a>,
+    idx: usize,
+}
+
+impl<'d, 'a> core::fmt::Debug for Node<'d, 'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.to_value().fmt(f)
+    }
+}
+
+impl<'d, 'a> Node<'d, 'a> {
+    fn slot(&self) -> Slot {
+        self.doc.tape[self.idx]
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self.slot(), Slot::Null)
+    }
+    pub fn is_array(&self) -> bool {
+        matches!(self.slot(), Slot::Array { .. })
+    }
+    pub fn is_object(&self) -> bool {
+        matches!(self.slot(), Slot::Object { .. })
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        opt_extract!(self.slot(), Slot::Bool(b) => Some(b))
+    }
+    pub fn as_num(&self) -> Option<Num> {
+        opt_extract!(self.slot(), Slot::Num(n) => Some(n))
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_num().and_then(Num::as_f64)
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_num().and_then(Num::as_i64)
+    }
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_num().and_then(Num::as_u64)
+    }
+    pub fn as_str(&self) -> Option<&'d str> {
+        self.doc.str_at(self.idx)
+    }
+
+    /// Number of elements in an array, or entries in an object.
+    pub fn len(&self) -> Option<usize> {
+        match self.slot() {
+            Slot::Array { len, .. } | Slot::Object { len, .. } => Some(len),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|n| n == 0)
+    }
+
+    pub fn get(&self, key: &str) -> Option<Node<'d, 'a>> {
+        // Like `Value` (and its `BTreeMap`), the last duplicate key wins.
+        self.entries()
+            .filter(|(k, _)| *k == key)
+            .last()
+            .map(|(_, v)| v)
+    }
+
+    pub fn at(&self, i: usize) -> Option<Node<'d, 'a>> {
+        self.iter().nth(i)
+    }
+
+    /// Iterate over the elements of an array. Empty for non-arrays.
+    pub fn iter(&self) -> Iter<'d, 'a> {
+        let (pos, end) = match self.slot() {
+            Slot::Array { end, .. } => (self.idx + 1, end),
+            _ => (0, 0),
+        };
+        Iter {
+            doc: self.doc,
+            pos,
+            end,
+        }
+    }
+
+    /// Iterate over the entries of an object. Empty for non-objects.
+    pub fn entries(&self) -> Entries<'d, 'a> {
+        let (pos, end) = match self.slot() {
+            Slot::Object { end, .. } => (self.idx + 1, end),
+            _ => (0, 0),
+        };
+        Entries {
+            doc: self.doc,
+            pos,
+            end,
+        }
+    }
+
+    pub fn to_value(&self) -> Value<'d> {
+        match self.slot() {
+            Slot::Null => Value::Null,
+            Slot::Bool(b) => Value::Bool(b),
+            Slot::Num(n) => Value::Num(n),
+            Slot::Src(..) | Slot::Buf(..) => Value::Str(Cow::Borrowed(self.as_str().unwrap())),
+            Slot::Array { .. } => self.iter().map(|n| n.to_value()).collect(),
+            Slot::Object { .. } => self.entries().map(|(k, v)| (k, v.to_value())).collect(),
+        }
+    }
+}
+
+pub struct Iter<'d, 'a> {
+    doc: &'d Document<'a>,
+    pos: usize,
+    end: usize,
+}
+
+impl<'d, 'a> Iterator for Iter<'d, 'a> {
+    type Item = Node<'d, 'a>;
+    fn next(&mut self) -> Option<Node<'d, 'a>> {
+        if self.pos >= self.end {
+            return None;
+        }
+        let idx = self.pos;
+        self.pos = self.doc.skip(idx);
+        Some(Node { doc: self.doc, idx })
+    }
+}
+
+pub struct Entries<'d, 'a> {
+    doc: &'d Document<'a>,
+    pos: usize,
+    end: usize,
+}
+
+impl<'d, 'a> Iterator for Entries<'d, 'a> {
+    type Item = (&'d str, Node<'d, 'a>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.end {
+            return None;
+        }
+        let k = self.doc.str_at(self.pos).unwrap();
+        let idx = self.pos + 1;
+        self.pos = self.doc.skip(idx);
+        Some((k, Node { doc: self.doc, idx }))
+    }
+}
+
+impl WriteJson for Node<'_, '_> {
+    fn write_json(&self, w: &mut Writer) {
+        match self.slot() {
+            Slot::Null => write::Null.write_json(w),
+            Slot::Bool(b) => b.write_json(w),
+            Slot::Num(n) => n.write_json(w),
+            Slot::Src(..) | Slot::Buf(..) => self.as_str().unwrap().write_json(w),
+            Slot::Array { .. } => {
+                let mut a = w.array();
+                for v in self.iter() {
+                    a.put(v);
+                }
+            }
+            Slot::Object { .. } => {
+                let mut o = w.object();
+                for (k, v) in self.entries() {
+                    o.put(k, &v);
+                }
+            }
+        }
+    }
+}
+
+impl WriteJson for Document<'_> {
+    fn write_json(&self, w: &mut Writer) {
+        self.root().write_json(w)
+    }
+}
+
+impl Document<'_> {
+    pub fn to_string(&self, pretty: bool) -> String {
+        let mut w = Writer::new(pretty);
+        self.write_json(&mut w);
+        w.finish()
+    }
+}
@@ -0,0 +1,140 @@
+//! A lossless tokenizer, for things like syntax highlighting.
+//!
+//! [`Tokenizer`] never fails. The spans it yields are contiguous and cover
+//! the whole input (whitespace and comments included), and anything which
+//! isn't valid is reported as a [`TokenKind::Error`] token.
+//!
+//! ```
+//! # use smoljson::{Dialect, token::{TokenKind, Tokenizer}};
+//! let src = r#"{"on": true} // x"#;
+//! let kinds = Tokenizer::new(src, Dialect::LOOSE)
+//!     .filter(|(k, _)| *k != TokenKind::Whitespace)
+//!     .map(|(k, r)| (k, &src[r]))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(kinds, [
+//!     (TokenKind::ObjectBegin, "{"),
+//!     (TokenKind::Key, "\"on\""),
+//!     (TokenKind::Colon, ":"),
+//!     (TokenKind::Keyword, "true"),
+//!     (TokenKind::ObjectEnd, "}"),
+//!     (TokenKind::LineComment, "// x"),
+//! ]);
+//! ```
+use crate::lex::{Kind, Lexer};
+use crate::read::Dialect;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    ObjectBegin,
+    ObjectEnd,
+    ArrayBegin,
+    ArrayEnd,
+    Colon,
+    Comma,
+    String,
+    /// A string used as an object key.
+    Key,
+    Number,
+    /// `true`, `false` or `null`.
+    Keyword,
+    LineComment,
+    BlockComment,
+    Whitespace,
+    /// Anything malformed: stray characters, unterminated strings and
+    /// comments, bad numbers, unknown words, and comments if the dialect
+    /// doesn't allow them.
+    Error,
+}
+
+impl TokenKind {
+    pub fn is_punctuation(self) -> bool {
+        use TokenKind::*;
+        matches!(
+            self,
+            ObjectBegin | ObjectEnd | ArrayBegin | ArrayEnd | Colon | Comma
+        )
+    }
+    /// Whitespace and comments.
+    pub fn is_trivia(self) -> bool {
+        use TokenKind::*;
+        matches!(self, Whitespace | LineComment | BlockComment)
+    }
+}
+
+/// Iterator over the tokens of a document. See the [module docs](self).
+#[derive(Clone)]
+pub struct Tokenizer<'a> {
+    lex: Lexer<'a>,
+    // Whether each enclosing container is an object.
+    stack: Vec<bool>,
+    expect_key: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(src: &'a str, dialect: Dialect) -> Self {
+        Self {
+            lex: Lexer::new(src, dialect),
+            stack: Vec::new(),
+            expect_key: false,
+        }
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = (TokenKind, Range<usize>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.lex.next()?;
+        let in_obj = self.stack.last() == Some(&true);
+        let kind = match t.kind {
+            _ if !t.ok => TokenKind::Error,
+            Kind::Whitespace => TokenKind::Whitespace,
+            Kind::LineComment => TokenKind::LineComment,
+            Kind::BlockComment => TokenKind::BlockComment,
+            Kind::Error => TokenKind::Error,
+            Kind::ObjectBegin | Kind::ArrayBegin => {
+                let obj = t.kind == Kind::ObjectBegin;
+                self.stack.push(obj);
+                self.expect_key = obj;
+                if obj {
+                    TokenKind::ObjectBegin
+                } else {
+                    TokenKind::ArrayBegin
+                }
+            }
+            Kind::ObjectEnd | Kind::ArrayEnd => {
+                self.stack.pop();
+                self.expect_key = false;
+                if t.kind == Kind::ObjectEnd {
+                    TokenKind::ObjectEnd
+                } else {
+                    TokenKind::ArrayEnd
+                }
+            }
+            Kind::Comma => {
+                self.expect_key = in_obj;
+                TokenKind::Comma
+            }
+            Kind::Colon => {
+                self.expect_key = false;
+                TokenKind::Colon
+            }
+            Kind::Str if self.expect_key && in_obj => {
+                self.expect_key = false;
+                TokenKind::Key
+            }
+            k => {
+                self.expect_key = false;
+                match k {
+                    Kind::Str => TokenKind::String,
+                    Kind::Num => TokenKind::Number,
+                    _ => TokenKind::Keyword,
+                }
+            }
+        };
+        Some((kind, t.span))
+    }
+}
+
+impl core::iter::FusedIterator for Tokenizer<'_> {}
@@ -0,0 +1,199 @@
+//! Validation which can run at compile time.
+//!
+//! [`validate`] is a `const fn`, so documents embedded in the binary can be
+//! checked during the build. [`json_const!`](crate::json_const) wraps it up:
+//!
+//! ```
+//! const DEFAULTS: &str = smoljson::json_const!(r#"{"threads": 4, "verbose": false}"#);
+//! assert_eq!(smoljson::Value::from_str(DEFAULTS).unwrap()["threads"].as_u64(), Some(4));
+//! ```
+//!
+//! ```compile_fail
+//! const DEFAULTS: &str = smoljson::json_const!(r#"{"threads": four}"#);
+//! ```
+//!
+//! This checks the grammar from RFC 8259, which is a bit stricter than the
+//! [`Reader`](crate::Reader) (e.g. it rejects control characters in strings
+//! and `1.`), so anything which passes will also parse at runtime.
+use crate::lex::{byte_at, number_end};
+use crate::read::Dialect;
+
+/// Containers nested deeper than this are rejected.
+pub const MAX_DEPTH: usize = 128;
+
+/// Why [`validate`] failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstError {
+    /// Byte offset of the problem.
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl core::fmt::Display for ConstError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at index {}", self.message, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConstError {}
+
+const fn err<T>(offset: usize, message: &'static str) -> Result<T, ConstError> {
+    Err(ConstError { offset, message })
+}
+
+/// Check that `s` is a single valid JSON document in `dialect`.
+pub const fn validate(s: &str, dialect: Dialect) -> Result<(), ConstError> {
+    let bs = s.as_bytes();
+    let comments = dialect.allow_comments;
+    // One bit per open container, set for objects.
+    let mut stack: u128 = 0;
+    let mut depth = 0;
+    let mut p = tri!(skip_trivia(bs, 0, comments));
+    'value: loop {
+        match byte_at(bs, p) {
+            b @ (b'{' | b'[') => {
+                if depth == MAX_DEPTH {
+                    return err(p, "nested too deeply");
+                }
+                let obj = b == b'{';
+                stack = (stack << 1) | obj as u128;
+                depth += 1;
+                p = tri!(skip_trivia(bs, p + 1, comments));
+                if byte_at(bs, p) == if obj { b'}' } else { b']' } {
+                    p += 1;
+                    stack >>= 1;
+                    depth -= 1;
+                } else if obj {
+                    p = tri!(key(bs, p, comments));
+                    continue 'value;
+                } else {
+                    continue 'value;
+                }
+            }
+            _ => p = tri!(scalar_end(bs, p)),
+        }
+        loop {
+            p = tri!(skip_trivia(bs, p, comments));
+            if depth == 0 {
+                if p == bs.len() {
+                    return Ok(());
+                }
+                return err(p, "trailing characters");
+            }
+            let obj = stack & 1 == 1;
+            match byte_at(bs, p) {
+                b',' => {
+                    p = tri!(skip_trivia(bs, p + 1, comments));
+                    let close = if obj { b'}' } else { b']' };
+                    if !dialect.allow_trailing_comma || byte_at(bs, p) != close {
+                        if obj {
+                            p = tri!(key(bs, p, comments));
+                        }
+                        continue 'value;
+                    }
+                }
+                b'}' if obj => {}
+                b']' if !obj => {}
+                _ => return err(p, "expected a comma"),
+            }
+            p += 1;
+            stack >>= 1;
+            depth -= 1;
+        }
+    }
+}
+
+// A key, its colon, and the trivia after both.
+const fn key(bs: &[u8], p: usize, comments: bool) -> Result<usize, ConstError> {
+    if byte_at(bs, p) != b'"' {
+        return err(p, "expected a key");
+    }
+    let p = tri!(skip_trivia(bs, tri!(string_end(bs, p)), comments));
+    if byte_at(bs, p) != b':' {
+        return err(p, "expected a colon");
+    }
+    skip_trivia(bs, p + 1, comments)
+}
+
+const fn skip_trivia(bs: &[u8], mut p: usize, comments: bool) -> Result<usize, ConstError> {
+    while p < bs.len() {
+        match bs[p] {
+            b' ' | b'\t' | b'\n' | b'\r' => p += 1,
+            b'/' if comments && byte_at(bs, p + 1) == b'/' => {
+                while p < bs.len() && bs[p] != b'\n' {
+                    p += 1;
+                }
+            }
+            b'/' if comments && byte_at(bs, p + 1) == b'*' => {
+                let start = p;
+                p += 2;
+                loop {
+                    if p + 1 >= bs.len() {
+                        return err(start, "unterminated comment");
+                    }
+                    if bs[p] == b'*' && bs[p + 1] == b'/' {
+                        break;
+                    }
+                    p += 1;
+                }
+                p += 2;
+            }
+            _ => break,
+        }
+    }
+    Ok(p)
+}
+
+const fn scalar_end(bs: &[u8], p: usize) -> Result<usize, ConstError> {
+    match byte_at(bs, p) {
+        b'"' => string_end(bs, p),
+        b't' => keyword_end(bs, p, b"true"),
+        b'f' => keyword_end(bs, p, b"false"),
+        b'n' => keyword_end(bs, p, b"null"),
+        b'-' | b'0'..=b'9' => match number_end(bs, p) {
+            Some(e) => Ok(e),
+            None => err(p, "invalid number"),
+        },
+        _ => err(p, "expected a value"),
+    }
+}
+
+const fn keyword_end(bs: &[u8], p: usize, kw: &[u8]) -> Result<usize, ConstError> {
+    let mut i = 0;
+    while i < kw.len() {
+        if byte_at(bs, p + i) != kw[i] {
+            return err(p, "expected a value");
+        }
+        i += 1;
+    }
+    Ok(p + kw.len())
+}
+
+const fn string_end(bs: &[u8], start: usize) -> Result<usize, ConstError> {
+    let mut p = start + 1;
+    loop {
+        if p >= bs.len() {
+            return err(start, "unterminated string");
+        }
+        match bs[p] {
+            b'"' => return Ok(p + 1),
+            b'\\' => match byte_at(bs, p + 1) {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => p += 2,
+                b'u' => {
+                    let mut i = 0;
+                    while i < 4 {
+                        if !byte_at(bs, p + 2 + i).is_ascii_hexdigit() {
+                            return err(p, "invalid escape");
+                        }
+                        i += 1;
+                    }
+                    p += 6;
+                }
+                _ => return err(p, "invalid escape"),
+            },
+            0..=0x1f => return err(p, "control character in string"),
+            _ => p += 1,
+        }
+    }
+}
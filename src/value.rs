@@ -6,13 +6,18 @@ use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum Value<'a> {
+    #[default]
     Null,
     Bool(bool),
     Num(Num),
     Str(Cow<'a, str>),
     Array(Vec<Value<'a>>),
+    /// Keys are always kept (and iterated, and written by default) in
+    /// `BTreeMap` order, so the order of the source is lost, and a `Value`
+    /// can't be reordered in place. To write them in another order, use
+    /// [`WriterOptions::key_order`](write::WriterOptions::key_order).
     Object(BTreeMap<Cow<'a, str>, Value<'a>>),
 }
 impl<'a> Value<'a> {
@@ -47,7 +52,7 @@ impl<'a> Value<'a> {
         opt_extract!(self, Self::Num(n) => n.as_u64())
     }
     pub fn as_str(&self) -> Option<&str> {
-        opt_extract!(self, Self::Str(s) => Some(&*s))
+        opt_extract!(self, Self::Str(s) => Some(s))
     }
     pub fn as_array(&self) -> Option<&[Value<'a>]> {
         opt_extract!(self, Self::Array(a) => Some(&a[..]))
@@ -85,7 +90,14 @@ impl<'a> Value<'a> {
     pub fn at_mut(&mut self, i: usize) -> Option<&mut Value<'a>> {
         self.as_mut_array().and_then(|s| s.get_mut(i))
     }
-    pub fn take(&mut self) -> Value {
+    /// Look up a value by [JSON Pointer](https://tools.ietf.org/html/rfc6901),
+    /// e.g. `v.pointer("/items/0/id")`.
+    ///
+    /// Returns `None` if nothing is there, or if `ptr` is not a valid pointer.
+    pub fn pointer(&self, ptr: &str) -> Option<&Value<'a>> {
+        crate::pointer::lookup(self, &crate::pointer::parse(ptr)?)
+    }
+    pub fn take(&mut self) -> Value<'a> {
         core::mem::replace(self, Self::Null)
     }
 }
@@ -128,11 +140,6 @@ fn bad_index(s: &str) -> ! {
     panic!("Attempt to insert key {:?} into non-object json value", s)
 }
 
-impl Default for Value<'_> {
-    fn default() -> Self {
-        Self::Null
-    }
-}
 impl From<bool> for Value<'_> {
     fn from(b: bool) -> Self {
         Self::Bool(b)
@@ -173,6 +180,7 @@ impl<'a, S: Into<Cow<'a, str>>> core::iter::FromIterator<(S, Value<'a>)> for Val
 }
 
 impl<'a> Value<'a> {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &'a str) -> Result<Self> {
         Self::from_str_with(s, Dialect::DEFAULT)
     }
@@ -206,43 +214,112 @@ impl<'a> Value<'a> {
         }
     }
     pub fn from_reader(de: &mut Reader<'a>) -> Result<Self> {
+        Self::read_with(de, &mut Borrowed)
+    }
+}
+
+impl Value<'static> {
+    /// Parse a `Value` which doesn't borrow from `s`.
+    ///
+    /// This is equivalent to `Value::from_str(s).map(Value::into_static)`, but
+    /// copies each string directly out of the input as it's parsed, rather
+    /// than making a second pass over the result. It's also what
+    /// `s.parse::<Value>()` uses.
+    pub fn from_str_owned(s: &str) -> Result<Self> {
+        Self::from_str_owned_with(s, Dialect::DEFAULT)
+    }
+
+    pub fn from_str_owned_with(input: &str, d: Dialect) -> Result<Self> {
+        let mut de = Reader::with_dialect(input, d);
+        let r = tri!(Self::from_reader_owned(&mut de));
+        tri!(de.finish());
+        Ok(r)
+    }
+
+    /// Like [`Value::from_reader`], but copying all strings.
+    pub fn from_reader_owned(de: &mut Reader<'_>) -> Result<Self> {
+        Self::read_with(de, &mut Owned)
+    }
+}
+
+/// Decides how strings from the `Reader` are turned into strings in the
+/// `Value` (e.g. whether they borrow from the input or not).
+pub(crate) trait Strs<'a, 'v> {
+    /// True if even strings which could be borrowed are copied.
+    const COPIES: bool = false;
+    /// `t` is always a `Token::StrBorrow` or `Token::StrOwn`.
+    fn string(&mut self, de: &mut Reader<'a>, t: Token<'a>) -> Cow<'v, str>;
+    fn key(&mut self, de: &mut Reader<'a>, t: Token<'a>) -> Cow<'v, str> {
+        self.string(de, t)
+    }
+}
+
+/// Borrow from the input wherever possible.
+pub(crate) struct Borrowed;
+impl<'a> Strs<'a, 'a> for Borrowed {
+    fn string(&mut self, _: &mut Reader<'a>, t: Token<'a>) -> Cow<'a, str> {
+        match t {
+            Token::StrBorrow(b) => Cow::Borrowed(b),
+            Token::StrOwn(b) => Cow::Owned(b),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Copy everything, so the result doesn't borrow from the input.
+pub(crate) struct Owned;
+impl<'a, 'v> Strs<'a, 'v> for Owned {
+    const COPIES: bool = true;
+    fn string(&mut self, _: &mut Reader<'a>, t: Token<'a>) -> Cow<'v, str> {
+        match t {
+            Token::StrBorrow(b) => Cow::Owned(b.into()),
+            Token::StrOwn(b) => Cow::Owned(b),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<'v> Value<'v> {
+    pub(crate) fn read_with<'a, S: Strs<'a, 'v>>(de: &mut Reader<'a>, s: &mut S) -> Result<Self> {
         match tri!(de.next()) {
             Token::Null => Ok(Self::Null),
             Token::Bool(b) => Ok(Self::Bool(b)),
             Token::NumF(b) => Ok(Self::from(b)),
             Token::NumI(b) => Ok(Self::from(b)),
             Token::NumU(b) => Ok(Self::from(b)),
-            Token::StrBorrow(b) => Ok(Self::Str(Cow::Borrowed(b))),
-            Token::StrOwn(b) => Ok(Self::Str(Cow::Owned(b.into()))),
-            Token::ArrayBegin => Self::do_read_array(de),
-            Token::ObjectBegin => Self::do_read_obj(de),
+            t @ (Token::StrBorrow(_) | Token::StrOwn(_)) => Ok(Self::Str(s.string(de, t))),
+            Token::ArrayBegin => Self::do_read_array(de, s),
+            Token::ObjectBegin => Self::do_read_obj(de, s),
             _ => Err(de.err()),
         }
     }
-    fn do_read_array(de: &mut Reader<'a>) -> Result<Self> {
+    fn do_read_array<'a, S: Strs<'a, 'v>>(de: &mut Reader<'a>, s: &mut S) -> Result<Self> {
         let mut v = alloc::vec![];
         if tri!(de.skipnpeek()) == Some(b']') {
             assert!(matches!(de.next_token(), Ok(Some(Token::ArrayEnd))));
             return Ok(Self::Array(v));
         }
         loop {
-            v.push(tri!(Value::from_reader(de)));
+            v.push(tri!(Value::read_with(de, s)));
             if !tri!(de.comma_or_array_end()) {
                 break;
             }
         }
         Ok(Self::Array(v))
     }
-    fn do_read_obj(de: &mut Reader<'a>) -> Result<Self> {
+    fn do_read_obj<'a, S: Strs<'a, 'v>>(de: &mut Reader<'a>, s: &mut S) -> Result<Self> {
         let mut obj = BTreeMap::new();
         if tri!(de.skipnpeek()) == Some(b'}') {
             assert!(matches!(de.next_token(), Ok(Some(Token::ObjectEnd))));
             return Ok(Self::Object(obj));
         }
         loop {
-            let k = tri!(de.key());
+            let k = match tri!(de.next()) {
+                t @ (Token::StrBorrow(_) | Token::StrOwn(_)) => s.key(de, t),
+                _ => return Err(de.err()),
+            };
             tri!(de.colon());
-            let val = tri!(Value::from_reader(de));
+            let val = tri!(Value::read_with(de, s));
             obj.insert(k, val);
             if !tri!(de.comma_or_obj_end()) {
                 break;
@@ -267,7 +344,7 @@ impl WriteJson for Value<'_> {
             Self::Null => write::Null.write_json(w),
             Self::Bool(b) => b.write_json(w),
             Self::Num(n) => n.write_json(w),
-            Self::Str(s) => (&*s).write_json(w),
+            Self::Str(s) => (**s).write_json(w),
             Self::Array(s) => {
                 let mut a = w.array();
                 for v in s {
@@ -277,7 +354,7 @@ impl WriteJson for Value<'_> {
             Self::Object(s) => {
                 let mut o = w.object();
                 for (k, v) in s {
-                    o.put(&*k, v);
+                    o.put(k, v);
                 }
             }
         }
@@ -285,16 +362,36 @@ impl WriteJson for Value<'_> {
 }
 impl Value<'_> {
     pub fn to_string(&self, pretty: bool) -> String {
-        let mut w = Writer::new(pretty);
+        self.to_string_with(write::WriterOptions::PRETTY.pretty(pretty))
+    }
+    /// Write this value with `opts`. Object keys are reordered according to
+    /// [`opts.key_order`](write::WriterOptions::key_order) as they're written;
+    /// `self` is left as is.
+    pub fn to_string_with(&self, opts: write::WriterOptions) -> String {
+        let mut w = Writer::with_options(opts);
         self.write_json(&mut w);
         w.finish()
     }
+    /// Like [`Value::to_string_with`], but fails if this contains a NaN or
+    /// infinity and `opts` uses [`NonFinite::Error`](write::NonFinite::Error).
+    pub fn try_to_string_with(
+        &self,
+        opts: write::WriterOptions,
+    ) -> core::result::Result<String, write::NonFiniteError> {
+        let mut w = Writer::with_options(opts);
+        self.write_json(&mut w);
+        w.try_finish()
+    }
+    /// The [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) canonical form
+    /// of this value. See [`WriterOptions::CANONICAL`](write::WriterOptions::CANONICAL).
+    pub fn to_canonical_string(&self) -> String {
+        self.to_string_with(write::WriterOptions::CANONICAL)
+    }
 }
 impl core::str::FromStr for Value<'static> {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self> {
-        // Not really ideal to do 2 passes...
-        Value::from_str(s).map(|v| v.into_static())
+        Value::from_str_owned(s)
     }
 }
 
@@ -317,7 +414,7 @@ const MAX_FLOAT_I: f64 = 9007199254740990.0;
 // If this returns true for some float, then we'll consider converting it to an
 // equivalent int internally.
 fn is_sanely_integral(f: f64) -> bool {
-    f.is_finite() && f >= -MAX_FLOAT_I && f <= MAX_FLOAT_I && (f as i64 as f64 == f)
+    f.is_finite() && (-MAX_FLOAT_I..=MAX_FLOAT_I).contains(&f) && (f as i64 as f64 == f)
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -335,8 +432,9 @@ impl Num {
             Self(N::U(v as u64))
         }
     }
+    /// Non-finite values are allowed, see [`NonFinite`](write::NonFinite) for
+    /// how they get written.
     pub fn from_f64(v: f64) -> Self {
-        debug_assert!(v.is_finite());
         if is_sanely_integral(v) {
             let result = if v < 0.0 {
                 Self(N::I(v as i64))
@@ -425,14 +523,14 @@ impl PartialEq for Num {
             }
             (F(f), I(i)) | (I(i), F(f)) => {
                 debug_assert!(i < 0, "{}", i);
-                if f < 0.0 && f >= -MAX_FLOAT_I && f as i64 as f64 == f {
+                if (-MAX_FLOAT_I..0.0).contains(&f) && f as i64 as f64 == f {
                     (i as f64) == f || (f as i64) == i
                 } else {
                     false
                 }
             }
             (F(f), U(i)) | (U(i), F(f)) => {
-                if f >= 0.0 && f <= MAX_FLOAT_I && f as u64 as f64 == f {
+                if (0.0..=MAX_FLOAT_I).contains(&f) && f as u64 as f64 == f {
                     (i as f64) == f || (f as u64) == i
                 } else {
                     false
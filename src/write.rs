@@ -1,5 +1,9 @@
+use crate::read::{Dialect, Reader, Result, Token};
 use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::fmt::Write;
+use core::ops::Range;
 
 #[derive(Clone, Debug, Default)]
 pub struct Writer {
@@ -7,6 +11,275 @@ pub struct Writer {
     o: String,
     indent: usize,
     pretty_stack: usize,
+    opts: WriterOptions,
+    // Set if a non-finite number was written with `NonFinite::Error`.
+    failed: bool,
+}
+
+/// How a [`Writer`] lays out its output.
+///
+/// Apart from `final_newline`, these only matter when pretty-printing
+/// (including inside containers which are [`compact`](ArrayWriter::compact)
+/// there, which are written as if `pretty` were false).
+///
+/// ```
+/// # use smoljson::{json, write::WriterOptions};
+/// let opts = WriterOptions::PRETTY.indent("\t").newline("\r\n").final_newline(true);
+/// let v = json!({"a": [1], "b": {}});
+/// assert_eq!(v.to_string_with(opts), "{\r\n\t\"a\": [\r\n\t\t1\r\n\t],\r\n\t\"b\": {}\r\n}\r\n");
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct WriterOptions {
+    pub pretty: bool,
+    /// Written once per level of nesting at the start of each line.
+    pub indent: &'static str,
+    pub newline: &'static str,
+    /// Put a space after the `:` between keys and values.
+    pub colon_space: bool,
+    /// Write empty containers as `[ ]` and `{ }` instead of `[]` and `{}`.
+    pub empty_space: bool,
+    /// End the output with `newline` (even when not pretty-printing).
+    pub final_newline: bool,
+    /// If set, containers which fit on one line in this many columns are
+    /// written that way (as `[1, 2]` and `{"a": 1}`), and the rest are broken
+    /// across lines as usual.
+    pub max_width: Option<usize>,
+    /// The order object keys are written in.
+    pub key_order: KeyOrder,
+    /// Write every number the way JavaScript's `Number.prototype.toString`
+    /// would, which means converting integers to `f64` first (and losing
+    /// precision above 2<sup>53</sup>).
+    pub ecmascript_numbers: bool,
+    /// Which characters in strings get escaped, beyond the ones which have
+    /// to be.
+    pub escape: Escape,
+    /// What to do with NaN and infinite `f64`s, which JSON can't represent.
+    pub non_finite: NonFinite,
+}
+
+/// How to write NaN and infinities. See [`WriterOptions::non_finite`].
+///
+/// This applies in the same way wherever the number is: object fields, array
+/// elements, and the top level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Write `null`, and make [`Writer::try_finish`] fail.
+    Error,
+    /// Write `null`, silently.
+    Null,
+    /// `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    String,
+    /// `NaN`, `Infinity` or `-Infinity`, which is valid JSON5 but not JSON.
+    Literal,
+    /// Write infinities as `f64::MAX` (or its negation) and NaN as `null`.
+    Clamp,
+}
+
+/// The error from [`Writer::try_finish`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonFiniteError;
+
+impl core::fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("JSON can't represent NaN or infinity")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NonFiniteError {}
+
+/// Extra escaping for strings. See [`WriterOptions::escape`].
+///
+/// Quotes, backslashes and control characters are always escaped, using the
+/// short forms (like `\n`) where JSON has them, and `\u00XX` otherwise.
+///
+/// ```
+/// # use smoljson::{json, write::{Escape, WriterOptions}};
+/// let opts = WriterOptions::COMPACT.escape(Escape::MINIMAL.html(true).ascii(true));
+/// let v = json!("</script> \u{e9}\u{1f600}");
+/// assert_eq!(v.to_string_with(opts), r#""\u003c/script\u003e \u00e9\ud83d\ude00""#);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct Escape {
+    /// Escape everything outside of ASCII, with surrogate pairs for
+    /// characters outside the BMP.
+    pub ascii: bool,
+    /// Escape `<`, `>`, `&` and `'`, so the output can go inside a `<script>`
+    /// tag or an HTML attribute.
+    pub html: bool,
+    /// Escape U+2028 and U+2029, which end lines in JavaScript before ES2019.
+    pub line_separators: bool,
+    /// Write `/` as `\/`.
+    pub slash: bool,
+}
+
+impl Escape {
+    /// Only what JSON requires.
+    pub const MINIMAL: Self = Self {
+        ascii: false,
+        html: false,
+        line_separators: false,
+        slash: false,
+    };
+    /// Safe to paste into JavaScript source, including inside a `<script>`
+    /// tag.
+    pub const JAVASCRIPT: Self = Self {
+        html: true,
+        line_separators: true,
+        ..Self::MINIMAL
+    };
+    pub const fn ascii(mut self, v: bool) -> Self {
+        self.ascii = v;
+        self
+    }
+    pub const fn html(mut self, v: bool) -> Self {
+        self.html = v;
+        self
+    }
+    pub const fn line_separators(mut self, v: bool) -> Self {
+        self.line_separators = v;
+        self
+    }
+    pub const fn slash(mut self, v: bool) -> Self {
+        self.slash = v;
+        self
+    }
+}
+
+/// How to order the keys of objects. See [`WriterOptions::key_order`].
+///
+/// This is applied at write time, by rearranging each object's entries in the
+/// output once the object is finished. Nothing is reordered in place:
+/// [`Value`](crate::Value) objects always keep their keys in `BTreeMap`
+/// order, and only a [`SpannedValue`](crate::spanned::SpannedValue) (which
+/// keeps source order) can be sorted with
+/// [`sort_keys`](crate::spanned::SpannedValue::sort_keys).
+///
+/// ```
+/// # use smoljson::{json, write::{KeyOrder, WriterOptions}};
+/// let order = KeyOrder::Priority(&["name", "version"], &KeyOrder::Lexicographic);
+/// let v = json!({"deps": {}, "version": "1.0", "name": "x", "author": "y"});
+/// assert_eq!(
+///     v.to_string_with(WriterOptions::COMPACT.key_order(order)),
+///     r#"{"name":"x","version":"1.0","author":"y","deps":{}}"#,
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub enum KeyOrder {
+    /// The order they're written in. For a [`Value`](crate::Value), that's
+    /// the order of its `BTreeMap`, i.e. [`KeyOrder::Lexicographic`].
+    Unchanged,
+    /// By UTF-8 bytes, which is the same as by code point.
+    Lexicographic,
+    /// By UTF-16 code units, as JavaScript's `Array.prototype.sort` would
+    /// (this differs from the above for characters above U+FFFF).
+    Utf16,
+    Custom(fn(&str, &str) -> Ordering),
+    /// The listed keys first, in the order listed, then the rest in the
+    /// fallback order.
+    Priority(&'static [&'static str], &'static KeyOrder),
+}
+
+impl KeyOrder {
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            KeyOrder::Unchanged => Ordering::Equal,
+            KeyOrder::Lexicographic => a.cmp(b),
+            KeyOrder::Utf16 => a.encode_utf16().cmp(b.encode_utf16()),
+            KeyOrder::Custom(f) => f(a, b),
+            KeyOrder::Priority(keys, then) => {
+                let rank = |k: &str| keys.iter().position(|p| *p == k).unwrap_or(keys.len());
+                rank(a).cmp(&rank(b)).then_with(|| then.compare(a, b))
+            }
+        }
+    }
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self::PRETTY
+    }
+}
+
+impl WriterOptions {
+    /// No whitespace at all. This is what `Writer::new(false)` uses.
+    pub const COMPACT: Self = Self {
+        pretty: false,
+        ..Self::PRETTY
+    };
+    /// Four space indentation and `\n` line endings. This is what
+    /// `Writer::new(true)` uses.
+    pub const PRETTY: Self = Self {
+        pretty: true,
+        indent: "    ",
+        newline: "\n",
+        colon_space: true,
+        empty_space: false,
+        final_newline: false,
+        max_width: None,
+        key_order: KeyOrder::Unchanged,
+        ecmascript_numbers: false,
+        escape: Escape::MINIMAL,
+        non_finite: NonFinite::Clamp,
+    };
+    /// The JSON Canonicalization Scheme from
+    /// [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785): no whitespace,
+    /// keys sorted by UTF-16 code units, and numbers written as in
+    /// JavaScript.
+    ///
+    /// Non-finite numbers and duplicate keys aren't allowed by the RFC, so
+    /// it's up to the caller to avoid them.
+    pub const CANONICAL: Self = Self {
+        key_order: KeyOrder::Utf16,
+        ecmascript_numbers: true,
+        ..Self::COMPACT
+    };
+    pub const fn pretty(mut self, v: bool) -> Self {
+        self.pretty = v;
+        self
+    }
+    pub const fn indent(mut self, v: &'static str) -> Self {
+        self.indent = v;
+        self
+    }
+    pub const fn newline(mut self, v: &'static str) -> Self {
+        self.newline = v;
+        self
+    }
+    pub const fn colon_space(mut self, v: bool) -> Self {
+        self.colon_space = v;
+        self
+    }
+    pub const fn empty_space(mut self, v: bool) -> Self {
+        self.empty_space = v;
+        self
+    }
+    pub const fn final_newline(mut self, v: bool) -> Self {
+        self.final_newline = v;
+        self
+    }
+    pub const fn max_width(mut self, v: Option<usize>) -> Self {
+        self.max_width = v;
+        self
+    }
+    pub const fn key_order(mut self, v: KeyOrder) -> Self {
+        self.key_order = v;
+        self
+    }
+    pub const fn ecmascript_numbers(mut self, v: bool) -> Self {
+        self.ecmascript_numbers = v;
+        self
+    }
+    pub const fn escape(mut self, v: Escape) -> Self {
+        self.escape = v;
+        self
+    }
+    pub const fn non_finite(mut self, v: NonFinite) -> Self {
+        self.non_finite = v;
+        self
+    }
 }
 
 pub trait WriteJson {
@@ -27,7 +300,22 @@ macro_rules! impl_write_json_prim_display {
         }
     )+};
 }
-impl_write_json_prim_display!(bool, i32, i64, u64, usize);
+impl_write_json_prim_display!(bool);
+
+macro_rules! impl_write_json_int {
+    ($($t:ty),+ $(,)?) => {$(
+        impl WriteJson for $t {
+            fn write_json(&self, dest: &mut Writer) {
+                if dest.opts.ecmascript_numbers {
+                    dest.put_ecmascript(*self as f64);
+                } else {
+                    let _ = write!(&mut dest.o, "{}", *self);
+                }
+            }
+        }
+    )+};
+}
+impl_write_json_int!(i32, i64, u64, usize);
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Null;
@@ -53,18 +341,14 @@ impl WriteJson for Null {
 
 impl WriteJson for f64 {
     fn write_json(&self, dest: &mut Writer) {
-        if self.is_nan() {
-            Undefined.write_json(dest);
-        } else if self.is_infinite() {
-            let max = if *self < 0.0 { -f64::MAX } else { f64::MAX };
-            let _ = write!(&mut dest.o, "{}", max);
+        if !self.is_finite() {
+            dest.put_non_finite(*self);
+        } else if dest.opts.ecmascript_numbers {
+            dest.put_ecmascript(*self);
         } else {
             let _ = write!(&mut dest.o, "{}", self);
         }
     }
-    fn should_include(&self) -> bool {
-        !self.is_nan()
-    }
 }
 
 impl WriteJson for str {
@@ -102,20 +386,39 @@ impl<T: WriteJson> WriteJson for Option<T> {
         }
     }
     fn should_include(&self) -> bool {
-        self.as_ref().map_or(false, T::should_include)
+        self.as_ref().is_some_and(T::should_include)
     }
 }
 
 impl Writer {
     pub fn new(pretty: bool) -> Self {
+        Self::with_options(WriterOptions::PRETTY.pretty(pretty))
+    }
+    pub fn with_options(opts: WriterOptions) -> Self {
         Self {
-            pretty_stack: if pretty { 0 } else { 1 },
+            pretty_stack: if opts.pretty { 0 } else { 1 },
+            opts,
             ..Self::default()
         }
     }
-    pub fn finish(self) -> String {
+    pub fn options(&self) -> &WriterOptions {
+        &self.opts
+    }
+    pub fn finish(mut self) -> String {
+        if self.opts.final_newline {
+            self.o.push_str(self.opts.newline);
+        }
         self.o
     }
+    /// Like [`Writer::finish`], but fails if a NaN or infinity was written
+    /// while using [`NonFinite::Error`].
+    pub fn try_finish(self) -> core::result::Result<String, NonFiniteError> {
+        if self.failed {
+            Err(NonFiniteError)
+        } else {
+            Ok(self.finish())
+        }
+    }
     pub fn pretty(&self) -> bool {
         self.pretty_stack == 0
     }
@@ -125,7 +428,7 @@ impl Writer {
     pub fn pop_compact(&mut self) {
         self.pretty_stack -= 1
     }
-    fn put_escaped<'a>(&'a mut self, s: &str, add_quotes: bool) {
+    fn put_escaped(&mut self, s: &str, add_quotes: bool) {
         self.o.reserve(s.len() + 2 * (add_quotes as usize));
         if add_quotes {
             self.o.push('"');
@@ -139,13 +442,17 @@ impl Writer {
                 '\t' => self.o.push_str("\\t"),
                 '\\' => self.o.push_str("\\\\"),
                 '"' => self.o.push_str("\\\""),
-                c if c < ' ' => {
-                    self.o.push_str("\\u00");
-                    let x = b"012345679abcdef";
-                    let n0 = x[(c as u32 as usize) >> 4];
-                    let n1 = x[(c as u32 as usize) & 0xf];
-                    self.o.push(n0 as char);
-                    self.o.push(n1 as char);
+                c if c < ' ' => self.put_u_escape(c as u16),
+                '/' if self.opts.escape.slash => self.o.push_str("\\/"),
+                '<' | '>' | '&' | '\'' if self.opts.escape.html => self.put_u_escape(c as u16),
+                '\u{2028}' | '\u{2029}' if self.opts.escape.line_separators => {
+                    self.put_u_escape(c as u16)
+                }
+                c if self.opts.escape.ascii && !c.is_ascii() => {
+                    let mut buf = [0; 2];
+                    for &u in c.encode_utf16(&mut buf).iter() {
+                        self.put_u_escape(u);
+                    }
                 }
                 _ => self.o.push(c),
             }
@@ -154,34 +461,140 @@ impl Writer {
             self.o.push('"');
         }
     }
+    fn put_u_escape(&mut self, u: u16) {
+        let x = b"0123456789abcdef";
+        self.o.push_str("\\u");
+        for shift in [12, 8, 4, 0] {
+            self.o.push(x[(u >> shift) as usize & 0xf] as char);
+        }
+    }
+    fn put_non_finite(&mut self, v: f64) {
+        let name = if v.is_nan() {
+            "NaN"
+        } else if v < 0.0 {
+            "-Infinity"
+        } else {
+            "Infinity"
+        };
+        match self.opts.non_finite {
+            NonFinite::Error => {
+                self.failed = true;
+                self.o.push_str("null");
+            }
+            NonFinite::Null => self.o.push_str("null"),
+            NonFinite::String => {
+                self.o.push('"');
+                self.o.push_str(name);
+                self.o.push('"');
+            }
+            NonFinite::Literal => self.o.push_str(name),
+            NonFinite::Clamp if v.is_nan() => self.o.push_str("null"),
+            NonFinite::Clamp => {
+                let max = if v < 0.0 { -f64::MAX } else { f64::MAX };
+                max.write_json(self);
+            }
+        }
+    }
+    // `Number.prototype.toString` for a finite `v` (ECMA-262's
+    // Number::toString, which RFC 8785 uses).
+    fn put_ecmascript(&mut self, v: f64) {
+        if v == 0.0 {
+            self.o.push('0');
+            return;
+        }
+        if v < 0.0 {
+            self.o.push('-');
+        }
+        // `{:e}` gives the shortest digits which round-trip, which is what
+        // JavaScript uses too. It just puts the decimal point elsewhere.
+        let sci = alloc::format!("{:e}", v.abs());
+        let (mantissa, exp) = sci.split_once('e').unwrap();
+        let mut digits = mantissa.replace('.', "");
+        if digits.len() >= 16 {
+            break_tie(&mut digits, exp, v.abs());
+        }
+        let k = digits.len() as i32;
+        let n = exp.parse::<i32>().unwrap() + 1;
+        if k <= n && n <= 21 {
+            self.o.push_str(&digits);
+            self.o.extend((k..n).map(|_| '0'));
+        } else if 0 < n && n <= 21 {
+            self.o.push_str(&digits[..n as usize]);
+            self.o.push('.');
+            self.o.push_str(&digits[n as usize..]);
+        } else if -6 < n && n <= 0 {
+            self.o.push_str("0.");
+            self.o.extend((n..0).map(|_| '0'));
+            self.o.push_str(&digits);
+        } else {
+            self.o.push_str(&digits[..1]);
+            if k > 1 {
+                self.o.push('.');
+                self.o.push_str(&digits[1..]);
+            }
+            let _ = write!(
+                &mut self.o,
+                "e{}{}",
+                if n > 0 { "+" } else { "-" },
+                (n - 1).abs()
+            );
+        }
+    }
     fn put_indent(&mut self) {
         if self.pretty() {
-            self.put_spaces(self.indent);
+            self.o.reserve(self.indent * self.opts.indent.len());
+            for _ in 0..self.indent {
+                self.o.push_str(self.opts.indent);
+            }
         }
     }
-    fn put_spaces(&mut self, size: usize) {
-        const SP: &str = "                                ";
-        const _: [(); 32] = [(); SP.len()];
-        self.o.reserve(size);
-        let mut n = size;
-        while n > 0 {
-            self.o.push_str(&SP[..n.min(SP.len())]);
-            n = n.saturating_sub(SP.len());
+    // Put the container starting at `start` on one line if it fits within
+    // `max` columns. Its children have already been folded if they could be,
+    // so this is the only time it's looked at.
+    fn fold(&mut self, start: usize, max: usize) {
+        let line = self.o[..start].rfind('\n').map_or(0, |i| i + 1);
+        let mut width = self.o[line..start].chars().count();
+        let mut out = String::new();
+        let mut chars = self.o[start..].chars();
+        let (mut in_str, mut escaped) = (false, false);
+        while let Some(c) = chars.next() {
+            if in_str {
+                in_str = escaped || c != '"';
+                escaped = !escaped && c == '\\';
+            } else if c == '\n' || c == '\r' {
+                // A line break and the indentation after it.
+                let rest = chars.as_str();
+                chars = rest.trim_start_matches(char::is_whitespace).chars();
+                if out.ends_with(',') {
+                    out.push(' ');
+                    width += 1;
+                }
+                continue;
+            } else {
+                in_str = c == '"';
+            }
+            out.push(c);
+            width += 1;
+            if width > max {
+                return;
+            }
         }
+        self.o.truncate(start);
+        self.o.push_str(&out);
     }
-    fn ppush<'a>(&'a mut self, if_pretty: &str, if_not_pretty: &str) {
-        self.o.push_str(if self.pretty() {
-            if_pretty
-        } else {
-            if_not_pretty
-        });
+    fn colon(&mut self) {
+        self.o.push(':');
+        if self.pretty() && self.opts.colon_space {
+            self.o.push(' ');
+        }
     }
     fn comma_nl(&mut self) {
-        self.ppush(",\n", ",");
+        self.o.push(',');
+        self.nl();
     }
     fn nl(&mut self) {
         if self.pretty() {
-            self.o.push('\n');
+            self.o.push_str(self.opts.newline);
         }
     }
     pub fn object(&mut self) -> ObjectWriter<'_> {
@@ -208,6 +621,137 @@ impl Writer {
     }
 }
 
+// If `v` is exactly halfway between two shortest representations, JavaScript
+// picks the one ending in an even digit, but `{:e}` always rounds up. This can
+// only happen when there are 16 or more digits, so it's rare enough that
+// looking at all of `v`'s digits is fine.
+fn break_tie(digits: &mut String, exp: &str, v: f64) {
+    let k = digits.len();
+    let full = alloc::format!("{:.800e}", v);
+    let (mantissa, full_exp) = full.split_once('e').unwrap();
+    let exact = mantissa.replace('.', "");
+    if full_exp != exp || exact.as_bytes()[k] != b'5' || exact[k + 1..].bytes().any(|b| b != b'0') {
+        return;
+    }
+    let mut even = String::from(&exact[..k]);
+    match exact.as_bytes()[k - 1] {
+        // Rounding up would end in a 0, so wouldn't have been the shortest.
+        b'9' => return,
+        d if (d - b'0') % 2 == 1 => {
+            even.pop();
+            even.push((d + 1) as char);
+        }
+        _ => {}
+    }
+    let candidate = alloc::format!("{}.{}e{}", &even[..1], &even[1..], exp);
+    if candidate.parse::<f64>() == Ok(v) {
+        *digits = even;
+    }
+}
+
+/// Reformat `input` without building a [`Value`](crate::Value) for it. See
+/// [`Writer::transcode`].
+///
+/// ```
+/// # use smoljson::{Dialect, write::transcode};
+/// let src = "{\"z\": 1.50, /* b */ \"a\": [1e3]}";
+/// assert_eq!(transcode(src, Dialect::LOOSE, false).unwrap(), r#"{"z":1.50,"a":[1e3]}"#);
+/// ```
+pub fn transcode(input: &str, dialect: Dialect, pretty: bool) -> Result<String> {
+    let mut de = Reader::with_dialect(input, dialect);
+    let mut w = Writer::new(pretty);
+    tri!(w.transcode(&mut de));
+    tri!(de.finish());
+    Ok(w.finish())
+}
+
+impl Writer {
+    /// Copy the next value from `de` to this writer, token by token.
+    ///
+    /// Nothing but the output is kept around, so this can pretty-print or
+    /// minify large documents cheaply, and using a `Reader` with comments
+    /// enabled turns CJSON into strict JSON. Object keys keep their order
+    /// (and duplicates), and numbers are copied exactly as written. Strings
+    /// are re-escaped, which may change how they're spelled, but not what
+    /// they contain.
+    ///
+    /// On error, whatever was written before it is left in the writer.
+    pub fn transcode(&mut self, de: &mut Reader<'_>) -> Result<()> {
+        let t = tri!(de.next());
+        match t {
+            Token::ArrayBegin => transcode_array(&mut self.array(), de),
+            Token::ObjectBegin => transcode_obj(&mut self.object(), de),
+            t => scalar(de, t, |v| v.write_json(self)),
+        }
+    }
+}
+
+struct RawNum<'a>(&'a str);
+
+impl WriteJson for RawNum<'_> {
+    fn write_json(&self, dest: &mut Writer) {
+        match self.0.parse::<f64>() {
+            Ok(v) if dest.opts.ecmascript_numbers => v.write_json(dest),
+            _ => dest.o.push_str(self.0),
+        }
+    }
+}
+
+fn scalar(de: &mut Reader<'_>, t: Token<'_>, put: impl FnOnce(&dyn WriteJson)) -> Result<()> {
+    match t {
+        Token::Null => put(&Null),
+        Token::Bool(b) => put(&b),
+        Token::NumF(_) | Token::NumI(_) | Token::NumU(_) => put(&RawNum(de.token_text())),
+        Token::StrBorrow(s) => put(&s),
+        Token::StrOwn(s) => {
+            put(&s.as_str());
+            de.reclaim(s);
+        }
+        _ => return Err(de.err()),
+    }
+    Ok(())
+}
+
+fn transcode_array(a: &mut ArrayWriter<'_>, de: &mut Reader<'_>) -> Result<()> {
+    if tri!(de.skipnpeek()) == Some(b']') {
+        tri!(de.next());
+        return Ok(());
+    }
+    loop {
+        match tri!(de.next()) {
+            Token::ArrayBegin => tri!(transcode_array(&mut a.begin_array(), de)),
+            Token::ObjectBegin => tri!(transcode_obj(&mut a.begin_object(), de)),
+            t => tri!(scalar(de, t, |v| {
+                a.put(v);
+            })),
+        }
+        if !tri!(de.comma_or_array_end()) {
+            return Ok(());
+        }
+    }
+}
+
+fn transcode_obj(o: &mut ObjectWriter<'_>, de: &mut Reader<'_>) -> Result<()> {
+    if tri!(de.skipnpeek()) == Some(b'}') {
+        tri!(de.next());
+        return Ok(());
+    }
+    loop {
+        let k = tri!(de.key());
+        tri!(de.colon());
+        match tri!(de.next()) {
+            Token::ArrayBegin => tri!(transcode_array(&mut o.begin_array(&k), de)),
+            Token::ObjectBegin => tri!(transcode_obj(&mut o.begin_object(&k), de)),
+            t => tri!(scalar(de, t, |v| {
+                o.put(&k, v);
+            })),
+        }
+        if !tri!(de.comma_or_obj_end()) {
+            return Ok(());
+        }
+    }
+}
+
 pub struct ArrayWriter<'a>(SeqWriter<'a>);
 
 impl<'a> ArrayWriter<'a> {
@@ -288,15 +832,29 @@ struct SeqWriter<'a> {
     first: bool,
     is_arr: bool,
     tmp_compact: bool,
+    // Where the opening bracket is in the output.
+    start: usize,
+    // Keys and output ranges of the entries so far, if they'll need sorting.
+    entries: Vec<(String, Range<usize>)>,
 }
 impl<'a> Drop for SeqWriter<'a> {
     fn drop(&mut self) {
-        self.w.indent -= 4;
+        if self.entries.len() > 1 {
+            self.reorder();
+        }
+        self.w.indent -= 1;
         if !self.first {
             self.w.nl();
             self.w.put_indent();
+        } else if self.w.pretty() && self.w.opts.empty_space {
+            self.w.o.push(' ');
         }
         self.w.o.push(if self.is_arr { ']' } else { '}' });
+        if let (Some(max), false) = (self.w.opts.max_width, self.first) {
+            if self.w.pretty() {
+                self.w.fold(self.start, max);
+            }
+        }
         if self.tmp_compact {
             self.w.pop_compact()
         }
@@ -304,13 +862,32 @@ impl<'a> Drop for SeqWriter<'a> {
 }
 impl<'a> SeqWriter<'a> {
     fn begin(w: &'a mut Writer, arr: bool) -> Self {
+        let start = w.o.len();
         w.o.push(if arr { '[' } else { '{' });
-        w.indent += 4;
+        w.indent += 1;
         Self {
+            start,
             w,
             first: true,
             tmp_compact: false,
             is_arr: arr,
+            entries: Vec::new(),
+        }
+    }
+    // Put the entries in the order the options ask for.
+    fn reorder(&mut self) {
+        let order = self.w.opts.key_order;
+        let mut entries = core::mem::take(&mut self.entries);
+        let from = entries[0].1.start;
+        entries.last_mut().unwrap().1.end = self.w.o.len();
+        entries.sort_by(|a, b| order.compare(&a.0, &b.0));
+        let text = self.w.o.split_off(from);
+        for (i, (_, r)) in entries.iter().enumerate() {
+            if i > 0 {
+                self.w.comma_nl();
+                self.w.put_indent();
+            }
+            self.w.o.push_str(&text[r.start - from..r.end - from]);
         }
     }
     fn set_compact(&mut self) {
@@ -319,6 +896,9 @@ impl<'a> SeqWriter<'a> {
         self.w.push_compact();
     }
     fn enter_key(&mut self, k: Option<&str>) {
+        if let Some(e) = self.entries.last_mut() {
+            e.1.end = self.w.o.len();
+        }
         if core::mem::replace(&mut self.first, false) {
             self.w.nl();
         } else {
@@ -326,8 +906,12 @@ impl<'a> SeqWriter<'a> {
         }
         self.w.put_indent();
         if let Some(k) = k {
+            if !matches!(self.w.opts.key_order, KeyOrder::Unchanged) {
+                let at = self.w.o.len();
+                self.entries.push((k.into(), at..at));
+            }
             self.w.put_escaped(k, true);
-            self.w.ppush(": ", ":");
+            self.w.colon();
         }
     }
     fn put_impl(&mut self, k: Option<&str>, v: &dyn WriteJson) {
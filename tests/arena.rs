@@ -0,0 +1,97 @@
+use smoljson::arena::Arena;
+use smoljson::write::Writer;
+use smoljson::{json, Dialect, Value};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts allocations made by the current thread.
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn realloc(&self, p: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.realloc(p, layout, size)
+    }
+    unsafe fn dealloc(&self, p: *mut u8, layout: Layout) {
+        System.dealloc(p, layout)
+    }
+}
+
+#[global_allocator]
+static A: Counting = Counting;
+
+fn allocs() -> usize {
+    ALLOCS.with(|n| n.get())
+}
+
+#[test]
+fn test_arena() {
+    let mut arena = Arena::new();
+    let docs = [
+        r#"{"id": 1, "name": "a\"b", "tags": ["x", "y"], "o": {"k": null}, "e": [], "eo": {}}"#,
+        r#"[1, -2, 2.5, true, false, null, "s", [[], [{}]]]"#,
+        r#""just a string""#,
+        r#"{"dup": 1, "dup": 2}"#,
+    ];
+    for d in &docs {
+        let want = Value::from_str(d).unwrap();
+        let doc = arena.parse(d).unwrap();
+        assert_eq!(doc.to_value(), want);
+        let mut w = Writer::new(false);
+        w.object().put("v", &doc.root());
+        let written = w.finish();
+        assert_eq!(Value::from_str(&written).unwrap(), json!({ "v": want }));
+    }
+    let cap = arena.capacity();
+    assert_ne!(cap, 0);
+    for _ in 0..10 {
+        arena.parse(docs[0]).unwrap();
+    }
+    assert_eq!(arena.capacity(), cap);
+
+    let doc = arena.parse(docs[0]).unwrap();
+    let v = doc.root();
+    assert_eq!(v.get("name").unwrap().as_str(), Some("a\"b"));
+    assert_eq!(v.get("tags").unwrap().at(1).unwrap().as_str(), Some("y"));
+    drop(doc);
+
+    assert_eq!(
+        arena
+            .parse(docs[3])
+            .unwrap()
+            .root()
+            .get("dup")
+            .unwrap()
+            .as_u64(),
+        Some(2)
+    );
+    assert!(arena.parse("[1,").is_err());
+    assert!(arena.parse("[1] x").is_err());
+    // Failing doesn't lose the buffers.
+    assert_eq!(arena.capacity(), cap);
+    assert!(arena.parse_with("[1 /* */]", Dialect::LOOSE).is_ok());
+}
+
+#[test]
+fn test_arena_no_allocs() {
+    let src = r#"{"a": [1, 2.5, {"b": "esc\n", "c": [[], {}]}], "d\u00e9": "x", "e": null}"#;
+    let mut arena = Arena::new();
+    let before = allocs();
+    arena.parse(src).unwrap();
+    assert_ne!(allocs(), before);
+    let before = allocs();
+    {
+        let doc = arena.parse(src).unwrap();
+        assert_eq!(doc.root().get("d\u{e9}").unwrap().as_str(), Some("x"));
+    }
+    assert!(arena.parse(&src[..20]).is_err());
+    assert_eq!(allocs() - before, 0);
+}
@@ -0,0 +1,103 @@
+use smoljson::write::{Writer, WriterOptions};
+use smoljson::{Reader, Value};
+
+fn canonical_num(v: f64) -> String {
+    let mut w = Writer::with_options(WriterOptions::CANONICAL);
+    w.array().put(v);
+    let s = w.finish();
+    s[1..s.len() - 1].to_string()
+}
+
+// RFC 8785 appendix B.
+#[test]
+fn test_numbers() {
+    let cases: &[(u64, &str)] = &[
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ];
+    for &(bits, want) in cases {
+        assert_eq!(canonical_num(f64::from_bits(bits)), want, "{:#x}", bits);
+    }
+    assert_eq!(canonical_num(100.0), "100");
+    assert_eq!(canonical_num(0.5), "0.5");
+    // Integers go through `f64` too.
+    let v = Value::from_str("[12345678901234567890, -3]").unwrap();
+    assert_eq!(v.to_canonical_string(), "[12345678901234567000,-3]");
+}
+
+// RFC 8785 section 3.2.2.
+const INPUT: &str = r#"{
+  "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+  "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+  "literals": [null, true, false]
+}"#;
+const OUTPUT: &str = "{\"literals\":[null,true,false],\"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\"string\":\"€$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\"}";
+
+#[test]
+fn test_example() {
+    assert_eq!(
+        Value::from_str(INPUT).unwrap().to_canonical_string(),
+        OUTPUT
+    );
+    // Streaming gives the same result, since objects get sorted as they're
+    // written.
+    let mut w = Writer::with_options(WriterOptions::CANONICAL);
+    w.transcode(&mut Reader::new(INPUT)).unwrap();
+    assert_eq!(w.finish(), OUTPUT);
+}
+
+// RFC 8785 section 3.2.3.
+#[test]
+fn test_sorting() {
+    let input = r#"{
+  "\u20ac": "Euro Sign",
+  "\r": "Carriage Return",
+  "\ufb33": "Hebrew Letter Dalet With Dagesh",
+  "1": "One",
+  "\ud83d\ude00": "Emoji: Grinning Face",
+  "\u0080": "Control",
+  "\u00f6": "Latin Small Letter O With Diaeresis"
+}"#;
+    let v = Value::from_str(input).unwrap();
+    let keys = v.to_canonical_string();
+    let values = Value::from_str(&keys).unwrap();
+    assert_eq!(values, v);
+    let order = [
+        "Carriage Return",
+        "One",
+        "Control",
+        "Latin Small Letter O With Diaeresis",
+        "Euro Sign",
+        "Emoji: Grinning Face",
+        "Hebrew Letter Dalet With Dagesh",
+    ];
+    let mut at = 0;
+    for name in order {
+        let i = keys.find(name).unwrap();
+        assert!(i > at, "{} is out of order in {}", name, keys);
+        at = i;
+    }
+    assert!(keys.starts_with("{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":"));
+}
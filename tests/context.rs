@@ -0,0 +1,89 @@
+use smoljson::{json, Dialect, ParseContext, Reader, Value};
+use std::borrow::Cow;
+
+#[test]
+fn test_reader_reset() {
+    let lines = ["{\"a\\n\": [1, \"x\\ty\"]}", "  2 ", "\"\\u00e9\""];
+    let mut r = Reader::new("");
+    for line in &lines {
+        r.reset(line);
+        let v = Value::from_reader(&mut r).unwrap();
+        assert_eq!(v, Value::from_str(line).unwrap());
+    }
+    r.reset("[1] 2");
+    assert!(Value::from_reader(&mut r).is_ok());
+    assert!(r.finish().is_err());
+}
+
+fn key_ptr(v: &Value<'_>, k: &str) -> *const u8 {
+    let (key, _) = v.as_object().unwrap().get_key_value(k).unwrap();
+    key.as_ptr()
+}
+
+#[test]
+fn test_context() {
+    let cx = ParseContext::with_dialect(Dialect::LOOSE).intern_keys(16);
+    let a = cx
+        .parse_owned(r#"{"name": "a", "id": 1, "esc\"aped": /**/ 2}"#)
+        .unwrap();
+    let b = cx
+        .parse_owned(r#"{"name": "b", "id": 2, "esc\"aped": 3}"#)
+        .unwrap();
+    assert_eq!(a, json!({"name": "a", "id": 1, "esc\"aped": 2}));
+    assert_eq!(b, json!({"name": "b", "id": 2, "esc\"aped": 3}));
+    // Keys are shared between documents
+    for k in &["name", "id", "esc\"aped"] {
+        assert_eq!(key_ptr(&a, k), key_ptr(&b, k));
+        assert!(matches!(
+            a.as_object().unwrap().get_key_value(*k),
+            Some((Cow::Borrowed(_), _))
+        ));
+    }
+    // ... until the budget runs out.
+    let c = cx.parse_owned(r#"{"a much longer key": 0}"#).unwrap();
+    let d = cx.parse_owned(r#"{"a much longer key": 0}"#).unwrap();
+    assert_ne!(
+        key_ptr(&c, "a much longer key"),
+        key_ptr(&d, "a much longer key")
+    );
+
+    // Borrowing mode only needs to intern escaped keys.
+    let src = r#"{"plain": "x\ny", "tab\t": 1}"#;
+    let e = cx.parse(src).unwrap();
+    assert_eq!(e, Value::from_str(src).unwrap());
+    assert!(src
+        .as_bytes()
+        .as_ptr_range()
+        .contains(&key_ptr(&e, "plain")));
+
+    assert!(cx.parse("[1, 2").is_err());
+    assert!(cx.parse("[1, 2] 3").is_err());
+    assert_eq!(cx.parse(" [1, 2] ").unwrap(), json!([1, 2]));
+    let strict = ParseContext::new();
+    assert_eq!(
+        strict.parse("[1 /**/]").is_ok(),
+        cfg!(feature = "default_allow_comments")
+    );
+}
+
+#[test]
+fn test_interned_keys_stable() {
+    // Enough keys that the set has to move them around internally.
+    let cx = ParseContext::new().intern_keys(1 << 20);
+    let docs = (0..500)
+        .map(|i| format!(r#"{{"key {}": {}, "shared": 0}}"#, i, i))
+        .collect::<Vec<_>>();
+    let mut seen = vec![];
+    for d in &docs {
+        let v = cx.parse_owned(d).unwrap();
+        let k = v.as_object().unwrap().keys().next().unwrap();
+        let k = (k.as_ptr(), k.to_string());
+        seen.push((v, k));
+    }
+    for (i, (v, (ptr, key))) in seen.iter().enumerate() {
+        assert_eq!(*key, format!("key {}", i));
+        assert_eq!(key_ptr(v, key), *ptr);
+        assert_eq!(key_ptr(v, "shared"), key_ptr(&seen[0].0, "shared"));
+        assert_eq!(v[&**key].as_u64(), Some(i as u64));
+    }
+}
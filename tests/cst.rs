@@ -0,0 +1,129 @@
+use smoljson::cst::{Cst, NodeKind};
+use smoljson::location::Segment;
+use smoljson::Dialect;
+
+const SRC: &str = r#"// Settings
+{
+    /* editor */
+    "editor": {
+        // Size of a tab.
+        // In spaces.
+        "tabSize": 4, // trailing
+        "rulers": [80, 100 /* wide */],
+
+        // detached?
+
+        "font": "mono"
+    },
+    "files.exclude": ["**/.git"]   ,
+    "empty":{}
+}
+"#;
+
+#[test]
+fn test_round_trip() {
+    let cst = Cst::parse(SRC, Dialect::CJSON);
+    assert_eq!(cst.errors(), &[]);
+    assert_eq!(cst.to_string(), SRC);
+    // Even broken documents print back exactly.
+    for src in [
+        "{\"a\": [1, 2",
+        "}{ /* x",
+        "[1,,2 @ \"x]",
+        "",
+        "  \u{1f600} ",
+    ] {
+        let cst = Cst::parse(src, Dialect::CJSON);
+        assert!(!cst.errors().is_empty(), "{:?}", src);
+        assert_eq!(cst.to_string(), src);
+    }
+}
+
+#[test]
+fn test_navigation() {
+    let cst = Cst::parse(SRC, Dialect::CJSON);
+    let root = cst.root().unwrap();
+    assert_eq!(root.kind(), NodeKind::Object);
+    assert_eq!(root.children().count(), 3);
+    let tab = root.pointer("/editor/tabSize").unwrap();
+    assert_eq!((tab.kind(), tab.text()), (NodeKind::Number, "4"));
+    let prop = tab.parent().unwrap();
+    assert_eq!(prop.kind(), NodeKind::Property);
+    assert_eq!(prop.key().unwrap(), "tabSize");
+    assert_eq!(&SRC[prop.key_span().unwrap()], "\"tabSize\"");
+    assert_eq!(prop.text(), "\"tabSize\": 4");
+    assert_eq!(
+        prop.parent().unwrap().parent().unwrap().key().unwrap(),
+        "editor"
+    );
+
+    let path = [
+        Segment::Key("editor".into()),
+        Segment::Key("rulers".into()),
+        Segment::Index(1),
+    ];
+    assert_eq!(root.find(&path).unwrap().text(), "100");
+    assert_eq!(
+        root.pointer("/files.exclude/0").unwrap().text(),
+        "\"**/.git\""
+    );
+    assert_eq!(root.get("empty").unwrap().text(), "{}");
+    assert!(root.pointer("/editor/nope").is_none());
+    assert!(root.pointer("/editor/rulers/2").is_none());
+}
+
+#[test]
+fn test_comments() {
+    let cst = Cst::parse(SRC, Dialect::CJSON);
+    let root = cst.root().unwrap();
+    assert_eq!(root.leading_comments(), ["// Settings"]);
+    let editor = root.property("editor").unwrap();
+    assert_eq!(editor.leading_comments(), ["/* editor */"]);
+    assert_eq!(editor.trailing_comment(), None);
+    let tab = editor.value().unwrap().property("tabSize").unwrap();
+    assert_eq!(
+        tab.leading_comments(),
+        ["// Size of a tab.", "// In spaces."]
+    );
+    assert_eq!(tab.trailing_comment(), Some("// trailing"));
+    // Trailing comments belong to the item before them, not the next one.
+    let rulers = editor.value().unwrap().property("rulers").unwrap();
+    assert!(rulers.leading_comments().is_empty());
+    let r100 = rulers.value().unwrap().at(1).unwrap();
+    assert_eq!(r100.trailing_comment(), Some("/* wide */"));
+    // Separated by a blank line.
+    let font = editor.value().unwrap().property("font").unwrap();
+    assert!(font.leading_comments().is_empty());
+}
+
+#[test]
+fn test_errors() {
+    let cst = Cst::parse(r#"{"a": [1, 2,], "b" 3, "c": }"#, Dialect::STRICT);
+    let msgs = cst.errors().iter().map(|e| e.message).collect::<Vec<_>>();
+    assert_eq!(
+        msgs,
+        [
+            "trailing comma",
+            "expected a colon",
+            "expected a comma",
+            "unexpected token",
+            "expected a value"
+        ]
+    );
+    let root = cst.root().unwrap();
+    assert_eq!(root.pointer("/a").unwrap().children().count(), 2);
+    assert!(root.property("b").unwrap().value().is_none());
+
+    let cst = Cst::parse("[1, 2,]", Dialect::STRICT.trailing_comma(true));
+    assert!(cst.errors().is_empty());
+    assert_eq!(cst.root().unwrap().children().count(), 2);
+
+    // Comments are errors unless the dialect allows them.
+    let cst = Cst::parse("[1] // hi", Dialect::STRICT);
+    assert!(!cst.errors().is_empty());
+    assert_eq!(cst.to_string(), "[1] // hi");
+
+    let cst = Cst::parse("{\"a\": [1, 2}", Dialect::STRICT);
+    assert_eq!(cst.errors()[0].message, "mismatched closing bracket");
+    assert_eq!(cst.root().unwrap().pointer("/a/1").unwrap().text(), "2");
+}
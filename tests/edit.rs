@@ -0,0 +1,189 @@
+use smoljson::edit::{apply_edits, modify, FormatOptions, ModifyError, ModifyOptions};
+use smoljson::location::Segment;
+use smoljson::write::WriteJson;
+use smoljson::{json, Dialect, Value};
+
+const SRC: &str = "{
+    // The answer.
+    \"a\": 1, // one
+    \"list\": [1, 2, 3],
+    \"b\": {
+        \"c\": true
+    }
+}";
+
+fn path(p: &[&str]) -> Vec<Segment<'static>> {
+    p.iter()
+        .map(|s| match s.parse() {
+            Ok(i) => Segment::Index(i),
+            Err(_) => Segment::Key(s.to_string().into()),
+        })
+        .collect()
+}
+
+#[track_caller]
+fn apply(src: &str, p: &[&str], v: Option<&dyn WriteJson>, opts: &ModifyOptions) -> String {
+    let edits = modify(src, &path(p), v, opts).unwrap();
+    apply_edits(src, &edits)
+}
+
+fn cjson() -> ModifyOptions {
+    ModifyOptions::default().dialect(Dialect::LOOSE)
+}
+
+#[test]
+fn test_replace() {
+    let opts = cjson();
+    let out = apply(SRC, &["a"], Some(&json!({"x": [1]})), &opts);
+    assert_eq!(
+        out,
+        SRC.replace(
+            "\"a\": 1,",
+            "\"a\": {\n        \"x\": [\n            1\n        ]\n    },"
+        )
+    );
+    let out = apply(SRC, &["list", "1"], Some(&json!("two")), &opts);
+    assert_eq!(out, SRC.replace("[1, 2, 3]", "[1, \"two\", 3]"));
+    let out = apply(SRC, &[], Some(&json!([])), &opts);
+    assert_eq!(out, "[]");
+}
+
+#[test]
+fn test_insert() {
+    let opts = cjson();
+    // Goes after the trailing comment, with the comma before it.
+    let src = "{\n  \"a\": 1 // one\n}";
+    let out = apply(src, &["b"], Some(&json!(2)), &opts);
+    assert_eq!(out, "{\n  \"a\": 1, // one\n  \"b\": 2\n}");
+    // Missing parents are created, indented to match.
+    let out = apply(SRC, &["b", "d", "e"], Some(&json!(null)), &opts);
+    assert_eq!(
+        out,
+        SRC.replace(
+            "\"c\": true\n",
+            "\"c\": true,\n        \"d\": {\n            \"e\": null\n        }\n"
+        )
+    );
+    // Arrays: append, and insert before.
+    let out = apply(SRC, &["list", "3"], Some(&json!(4)), &opts);
+    assert_eq!(out, SRC.replace("[1, 2, 3]", "[1, 2, 3, 4]"));
+    let out = apply(SRC, &["list", "0"], Some(&json!(0)), &opts.insert(true));
+    assert_eq!(out, SRC.replace("[1, 2, 3]", "[0, 1, 2, 3]"));
+    // Empty containers.
+    assert_eq!(apply("{}", &["a"], Some(&json!(1)), &opts), "{\"a\": 1}");
+    assert_eq!(
+        apply("{}", &["a", "b"], Some(&json!(2)), &opts),
+        "{\"a\": {\"b\": 2}}"
+    );
+    assert_eq!(
+        apply(
+            "[1]",
+            &["1", "k", "0"],
+            Some(&json!({"x": [], "y": 1})),
+            &opts
+        ),
+        "[1, {\"k\": [{\"x\": [], \"y\": 1}]}]"
+    );
+    assert_eq!(
+        apply("{\"x\": {}\n}", &["x", "a"], Some(&json!(1)), &opts),
+        "{\"x\": {\n    \"a\": 1\n}\n}"
+    );
+    assert_eq!(
+        apply("", &["a", "0"], Some(&json!(true)), &opts),
+        "{\n    \"a\": [\n        true\n    ]\n}"
+    );
+    assert_eq!(apply("// hi", &[], Some(&json!(1)), &opts), "// hi\n1");
+}
+
+#[test]
+fn test_trailing_comma_kept() {
+    let opts = cjson();
+    let src = "{\n  \"a\": 1,\n}";
+    let out = apply(src, &["b"], Some(&json!(2)), &opts);
+    assert_eq!(out, "{\n  \"a\": 1,\n  \"b\": 2,\n}");
+    let out = apply("[1, 2,]", &["2"], Some(&json!(3)), &opts);
+    assert_eq!(out, "[1, 2, 3,]");
+    // Even with a comment between the item and its comma.
+    let src = "{\n    \"x\": 1 /* c */,\n}";
+    let out = apply(src, &["y"], Some(&json!(2)), &opts);
+    assert_eq!(out, "{\n    \"x\": 1 /* c */,\n    \"y\": 2,\n}");
+    assert!(Value::from_str_with(&out, Dialect::LOOSE).is_ok());
+    let src = "{\"x\": 1 /* c */, }";
+    let out = apply(src, &["y"], Some(&json!(2)), &opts);
+    assert_eq!(out, "{\"x\": 1 /* c */, \"y\": 2, }");
+    assert!(Value::from_str_with(&out, Dialect::LOOSE).is_ok());
+}
+
+#[test]
+fn test_remove() {
+    let opts = cjson();
+    // First, with its line.
+    let out = apply(SRC, &["list"], None, &opts);
+    assert_eq!(out, SRC.replace("    \"list\": [1, 2, 3],\n", ""));
+    // Last: takes the comma before it with it.
+    let out = apply(SRC, &["b"], None, &opts);
+    assert_eq!(
+        out,
+        "{\n    // The answer.\n    \"a\": 1, // one\n    \"list\": [1, 2, 3]\n}"
+    );
+    // Comments are left alone.
+    let out = apply(SRC, &["a"], None, &opts);
+    assert_eq!(out, SRC.replace("\"a\": 1, ", ""));
+    let src = "[1, /* two */ 2]";
+    assert_eq!(apply(src, &["1"], None, &opts), "[1 /* two */ ]");
+    assert_eq!(apply("[1, 2, 3]", &["1"], None, &opts), "[1, 3]");
+    assert_eq!(apply("[1, 2, 3]", &["2"], None, &opts), "[1, 2]");
+    assert_eq!(apply("{\n  \"a\": 1\n}", &["a"], None, &opts), "{\n}");
+    // Nothing to remove.
+    assert_eq!(apply(SRC, &["nope", "x"], None, &opts), SRC);
+}
+
+#[test]
+fn test_format_options() {
+    let opts = cjson().format(FormatOptions {
+        insert_spaces: false,
+        eol: "\r\n",
+        ..FormatOptions::default()
+    });
+    let src = "{\r\n\t\"a\": 1\r\n}";
+    let out = apply(src, &["b"], Some(&json!({"c": 1})), &opts);
+    assert_eq!(
+        out,
+        "{\r\n\t\"a\": 1,\r\n\t\"b\": {\r\n\t\t\"c\": 1\r\n\t}\r\n}"
+    );
+    assert!(Value::from_str(&out).is_ok());
+
+    let opts = cjson().format(FormatOptions {
+        tab_size: 2,
+        ..FormatOptions::default()
+    });
+    let src = "{\n  \"a\": 1\n}";
+    let out = apply(src, &["b"], Some(&json!({"c": [1]})), &opts);
+    assert_eq!(
+        out,
+        "{\n  \"a\": 1,\n  \"b\": {\n    \"c\": [\n      1\n    ]\n  }\n}"
+    );
+}
+
+#[test]
+fn test_errors() {
+    let opts = cjson();
+    let err = |p: &[&str]| modify(SRC, &path(p), Some(&json!(1)), &opts).unwrap_err();
+    assert_eq!(err(&["a", "x"]), ModifyError::NotAContainer);
+    assert_eq!(err(&["list", "x"]), ModifyError::NotAContainer);
+    assert_eq!(err(&["list", "5"]), ModifyError::IndexOutOfBounds);
+    assert_eq!(err(&["new", "1"]), ModifyError::IndexOutOfBounds);
+    assert_eq!(err(&["b", "new", "0", "2"]), ModifyError::IndexOutOfBounds);
+    assert_eq!(
+        modify("", &path(&["x", "3"]), Some(&json!(1)), &opts),
+        Err(ModifyError::IndexOutOfBounds)
+    );
+    assert_eq!(
+        apply(SRC, &["b", "new", "0", "k"], Some(&json!(1)), &opts),
+        apply(SRC, &["b", "new"], Some(&json!([{"k": 1}])), &opts)
+    );
+    assert_eq!(
+        modify("{\"a\": }", &path(&["a"]), Some(&json!(1)), &opts),
+        Err(ModifyError::Malformed)
+    );
+}
@@ -0,0 +1,82 @@
+use smoljson::edit::{apply_edits, FormatOptions};
+use smoljson::format::format;
+use smoljson::Dialect;
+
+#[track_caller]
+fn fmt(src: &str, opts: &FormatOptions) -> String {
+    apply_edits(src, &format(src, None, Dialect::LOOSE, opts))
+}
+
+#[test]
+fn test_format() {
+    let opts = FormatOptions::default();
+    assert_eq!(
+        fmt(r#"  {"a":[1,2,{}],"b" :{ "c":null } ,"d":[ ]}  "#, &opts),
+        "{\n    \"a\": [\n        1,\n        2,\n        {}\n    ],\n    \"b\": {\n        \"c\": null\n    },\n    \"d\": []\n}"
+    );
+    assert_eq!(fmt("1", &opts), "1");
+    assert_eq!(fmt("", &opts), "");
+    // Already formatted means no edits at all.
+    let done = "{\n    \"a\": [\n        1\n    ]\n}";
+    assert!(format(done, None, Dialect::STRICT, &opts).is_empty());
+}
+
+#[test]
+fn test_comments() {
+    let opts = FormatOptions::default();
+    let src = "{ // top\n\"a\":1,/* x */\"b\":2,\n   // about c\n\"c\": 3 /* end */}";
+    assert_eq!(
+        fmt(src, &opts),
+        "{ // top\n    \"a\": 1, /* x */\n    \"b\": 2,\n    // about c\n    \"c\": 3 /* end */\n}"
+    );
+    // Without comment support, it gives up at the comment.
+    let src = "[1,2, // x\n3]";
+    let out = apply_edits(src, &format(src, None, Dialect::STRICT, &opts));
+    assert_eq!(out, "[\n    1,\n    2, // x\n3]");
+}
+
+#[test]
+fn test_options() {
+    let opts = FormatOptions {
+        insert_spaces: false,
+        eol: "\r\n",
+        insert_final_newline: true,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        fmt("{\"a\": [1]}", &opts),
+        "{\r\n\t\"a\": [\r\n\t\t1\r\n\t]\r\n}\r\n"
+    );
+    let opts = FormatOptions {
+        tab_size: 2,
+        ..FormatOptions::default()
+    };
+    assert_eq!(fmt("[[1]]", &opts), "[\n  [\n    1\n  ]\n]");
+}
+
+#[test]
+fn test_range() {
+    let opts = FormatOptions::default();
+    let src = "{\n    \"a\": {\"b\":1,\n    \"c\":[1,2]},\n  \"d\":  2\n}";
+    // Only the selected line changes, and it's indented relative to where
+    // it starts.
+    let start = src.find("\"c\"").unwrap();
+    let end = start + src[start..].find('\n').unwrap();
+    let edits = format(src, Some(start..end), Dialect::STRICT, &opts);
+    assert_eq!(
+        apply_edits(src, &edits),
+        "{\n    \"a\": {\"b\":1,\n    \"c\": [\n        1,\n        2\n    ]\n},\n  \"d\":  2\n}"
+    );
+    for e in &edits {
+        assert!(e.offset >= src[..start].rfind('\n').unwrap());
+        assert!(e.offset + e.len <= start + src[start..].find('\n').unwrap());
+    }
+}
+
+#[test]
+fn test_malformed() {
+    let opts = FormatOptions::default();
+    // Edits before the problem are still made.
+    assert_eq!(fmt("[1,2 3,4]", &opts), "[\n    1,\n    2 3,4]");
+    assert_eq!(fmt("{\"a\": \"b", &opts), "{\n    \"a\": \"b");
+}
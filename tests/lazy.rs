@@ -0,0 +1,66 @@
+use smoljson::{json, Dialect, LazyDocument, Value};
+
+#[test]
+fn test_lazy() {
+    let src = r#" {
+        "meta": {"version": 3, "tags": ["x", "y\"z"]},
+        "items": [{"id": 1}, {"id": 2}, [], {}],
+        "dup": 1,
+        "dup": 2
+    } "#;
+    let doc = LazyDocument::new(src);
+    let full = Value::from_str(src).unwrap();
+    let meta = doc.get("meta").unwrap().unwrap();
+    assert!(meta.is_object());
+    let tags = meta.get("tags").unwrap().unwrap();
+    assert!(tags.is_array());
+    assert_eq!(tags.value().unwrap(), full["meta"]["tags"]);
+    assert_eq!(tags.at(1).unwrap().unwrap().value().unwrap(), json!("y\"z"));
+    assert!(tags.at(2).unwrap().is_none());
+    assert!(tags.get("nope").unwrap().is_none());
+    assert!(meta.at(0).unwrap().is_none());
+
+    let items = doc.get("items").unwrap().unwrap();
+    // Ask out of order, so some lookups hit the cache.
+    for &i in &[1, 0, 3, 2, 1] {
+        let item = items.at(i).unwrap().unwrap();
+        assert_eq!(item.value().unwrap(), full["items"][i]);
+    }
+    assert!(items.at(2).unwrap().unwrap().at(0).unwrap().is_none());
+    assert!(items.at(3).unwrap().unwrap().get("").unwrap().is_none());
+    assert_eq!(doc.get("dup").unwrap().unwrap().value().unwrap(), json!(2));
+    assert_eq!(
+        doc.get("dup").unwrap().unwrap().value().unwrap(),
+        full["dup"]
+    );
+    assert!(doc.get("missing").unwrap().is_none());
+    assert!(doc.at(0).unwrap().is_none());
+    assert_eq!(doc.value().unwrap(), full);
+}
+
+#[test]
+fn test_lazy_errors() {
+    // The broken part is never visited.
+    let doc = LazyDocument::new(r#"[{"a": 1}, [1, }"#);
+    assert_eq!(
+        doc.at(0)
+            .unwrap()
+            .unwrap()
+            .get("a")
+            .unwrap()
+            .unwrap()
+            .value()
+            .unwrap(),
+        json!(1)
+    );
+    assert!(doc.at(1).unwrap().unwrap().at(1).is_err());
+    assert!(doc.at(2).is_err());
+    assert!(doc.at(1).unwrap().unwrap().at(0).is_ok());
+    // Looking up any key scans the whole object, for duplicates.
+    let doc = LazyDocument::new(r#"{"a": 1, "b": [1, }"#);
+    assert!(doc.get("a").is_err());
+    assert!(LazyDocument::new("  ").root().is_err());
+
+    let doc = LazyDocument::with_dialect("/* hi */ [1, // one\n 2]", Dialect::CJSON);
+    assert_eq!(doc.at(1).unwrap().unwrap().value().unwrap(), json!(2));
+}
@@ -0,0 +1,63 @@
+use smoljson::line_index::{Encoding, LineCol, LineIndex};
+
+fn lc(line: usize, col: usize) -> LineCol {
+    LineCol { line, col }
+}
+
+#[test]
+fn test_lines() {
+    let src = "a\r\nb\nc\rd\n";
+    let idx = LineIndex::new(src);
+    assert_eq!(idx.line_count(), 5);
+    let lines = (0..idx.line_count())
+        .map(|l| &src[idx.line_span(l).unwrap()])
+        .collect::<Vec<_>>();
+    assert_eq!(lines, ["a", "b", "c", "d", ""]);
+    assert_eq!(idx.line_span(5), None);
+    assert_eq!(idx.line_col(0, Encoding::Utf8), Some(lc(0, 0)));
+    // The line break belongs to the line it ends.
+    assert_eq!(idx.line_col(1, Encoding::Utf8), Some(lc(0, 1)));
+    assert_eq!(idx.line_col(2, Encoding::Utf8), Some(lc(0, 2)));
+    assert_eq!(idx.line_col(3, Encoding::Utf8), Some(lc(1, 0)));
+    assert_eq!(idx.line_col(src.len(), Encoding::Utf8), Some(lc(4, 0)));
+    assert_eq!(idx.line_col(src.len() + 1, Encoding::Utf8), None);
+    assert_eq!(
+        LineIndex::new("").line_col(0, Encoding::Utf16),
+        Some(lc(0, 0))
+    );
+}
+
+#[test]
+fn test_columns() {
+    // 'é' is 2 bytes and 1 UTF-16 unit, '😀' is 4 bytes and 2 UTF-16 units.
+    let src = "[\n\"é😀x\"]";
+    let idx = LineIndex::new(src);
+    let x = src.find('x').unwrap();
+    assert_eq!(idx.line_col(x, Encoding::Utf8), Some(lc(1, 7)));
+    assert_eq!(idx.line_col(x, Encoding::Utf16), Some(lc(1, 4)));
+    assert_eq!(idx.line_col(x, Encoding::Char), Some(lc(1, 3)));
+    assert_eq!(idx.line_col(x - 1, Encoding::Utf16), None);
+    for enc in [Encoding::Utf8, Encoding::Utf16, Encoding::Char] {
+        for (off, _) in src.char_indices() {
+            let pos = idx.line_col(off, enc).unwrap();
+            assert_eq!(idx.offset(pos, enc), Some(off), "{:?} {}", enc, off);
+        }
+    }
+    // Inside a surrogate pair rounds down, and past the end clamps.
+    let emoji = src.find('😀').unwrap();
+    assert_eq!(idx.offset(lc(1, 3), Encoding::Utf16), Some(emoji));
+    assert_eq!(idx.offset(lc(1, 99), Encoding::Utf16), Some(src.len()));
+    assert_eq!(idx.offset(lc(0, 99), Encoding::Char), Some(1));
+    assert_eq!(idx.offset(lc(2, 0), Encoding::Char), None);
+}
+
+#[test]
+fn test_ranges() {
+    let src = "{\n  \"k\": \"😀\"\n}";
+    let idx = LineIndex::new(src);
+    let v = smoljson::spanned::SpannedValue::from_str(src).unwrap();
+    let span = v.pointer_span("/k").unwrap();
+    let r = idx.range(span.clone(), Encoding::Utf16).unwrap();
+    assert_eq!(r, lc(1, 7)..lc(1, 11));
+    assert_eq!(idx.offset_range(r, Encoding::Utf16), Some(span));
+}
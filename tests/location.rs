@@ -0,0 +1,139 @@
+use smoljson::location::{location, Location, NodeKind, Segment};
+use smoljson::Dialect;
+
+// `|` marks the cursor. `f` gets the location, for any further checks.
+#[track_caller]
+fn check_with(
+    src: &str,
+    path: &[&str],
+    prev: Option<NodeKind>,
+    at_key: bool,
+    f: impl FnOnce(&Location<'_>),
+) {
+    let offset = src.find('|').unwrap();
+    let src = src.replacen('|', "", 1);
+    let loc = location(&src, offset, Dialect::CJSON);
+    let got = loc
+        .path
+        .iter()
+        .map(|s| match s {
+            Segment::Key(k) => k.to_string(),
+            Segment::Index(i) => i.to_string(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(got, path, "{:?} @ {}", src, offset);
+    assert_eq!(
+        loc.previous.as_ref().map(|n| n.kind),
+        prev,
+        "{:?} @ {}",
+        src,
+        offset
+    );
+    assert_eq!(loc.at_key, at_key, "{:?} @ {}", src, offset);
+    f(&loc);
+}
+
+#[track_caller]
+fn check(src: &str, path: &[&str], prev: Option<NodeKind>, at_key: bool) {
+    check_with(src, path, prev, at_key, |_| {});
+}
+
+#[test]
+fn test_objects() {
+    use NodeKind::*;
+    check(r#"|{ "foo": "bar" }"#, &[], None, false);
+    check(r#"{| "foo": "bar" }"#, &[""], None, true);
+    check(r#"{ |"foo": "bar" }"#, &["foo"], Some(Property), true);
+    check(r#"{ "fo|o": "bar" }"#, &["foo"], Some(Property), true);
+    check(r#"{ "foo"|: "bar" }"#, &["foo"], Some(Property), true);
+    check(r#"{ "foo":| "bar" }"#, &["foo"], None, false);
+    check(r#"{ "foo": "bar"| }"#, &["foo"], Some(String), false);
+    check(
+        r#"{ "foo": {"bar": 1|, "car": 2} }"#,
+        &["foo", "bar"],
+        Some(Number),
+        false,
+    );
+    check(
+        r#"{ "foo": {"bar": 1,| "car": 2} }"#,
+        &["foo", ""],
+        None,
+        true,
+    );
+    check(r#"{ "foo": {"bar": 1, "car": 2}| }"#, &["foo"], None, false);
+    check(
+        r#"{ "foo": {"bar": 1}, "go|o": {} }"#,
+        &["goo"],
+        Some(Property),
+        true,
+    );
+}
+
+#[test]
+fn test_arrays() {
+    use NodeKind::*;
+    check(r#"{ "foo": [ | ] }"#, &["foo", "0"], None, false);
+    check(r#"{ "foo": [ 1 ,| ] }"#, &["foo", "1"], None, false);
+    check(
+        r#"{ "foo": [ 1,"|" ] }"#,
+        &["foo", "1"],
+        Some(String),
+        false,
+    );
+    check(
+        r#"[ true, [null, {"a": fal|se}] ]"#,
+        &["1", "1", "a"],
+        Some(Bool),
+        false,
+    );
+    check(r#"[ 1, [2], | ]"#, &["2"], None, false);
+}
+
+#[test]
+fn test_incomplete() {
+    use NodeKind::*;
+    check(r#"{ "a": { "b|"#, &["a", "b"], Some(Property), true);
+    // Unterminated, so there's no value.
+    check_with(
+        r#"{ "a": { "b": "x|"#,
+        &["a", "b"],
+        Some(String),
+        false,
+        |loc| {
+            assert_eq!(loc.previous.as_ref().unwrap().value, None);
+        },
+    );
+    check(r#"{ "a": [1, {|"#, &["a", "1", ""], None, true);
+    check(r#"{ "a" : @@ |"#, &["a"], None, false);
+}
+
+#[test]
+fn test_comments() {
+    use NodeKind::*;
+    check("{ \"a\": 1, // \"b\": |\n}", &[""], None, true);
+    check("{ \"a\": /* , */ |1 }", &["a"], Some(Number), false);
+    // Without comment support, the comment's contents are tokens like any
+    // other.
+    let src = "{ \"a\": 1 // , \"b\": ";
+    let loc = location(src, src.len(), Dialect::STRICT);
+    assert_eq!(loc.path, [Segment::Key("b".into())]);
+    assert!(!loc.at_key);
+}
+
+#[test]
+fn test_matches() {
+    check_with(
+        r#"{"a": [{"b": {"c": |}}]}"#,
+        &["a", "0", "b", "c"],
+        None,
+        false,
+        |loc| {
+            assert!(loc.matches(&["a", "0", "b", "c"]));
+            assert!(loc.matches(&["a", "*", "b", "*"]));
+            assert!(loc.matches(&["**", "c"]));
+            assert!(loc.matches(&["a", "**"]));
+            assert!(!loc.matches(&["a", "*", "b"]));
+            assert!(!loc.matches(&["a", "1", "**"]));
+        },
+    );
+}
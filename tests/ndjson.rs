@@ -0,0 +1,62 @@
+#![cfg(feature = "std")]
+use smoljson::ndjson::NdjsonParser;
+use smoljson::{json, Dialect, Value};
+use std::sync::Mutex;
+
+fn input() -> String {
+    let mut s = String::new();
+    for i in 0..2000 {
+        match i % 100 {
+            7 => s.push_str("{\"broken\": \n"),
+            13 => s.push_str("   \r\n"),
+            _ => s.push_str(&format!("{{\"i\": {}, \"s\": \"x\\ty\"}}\r\n", i)),
+        }
+    }
+    s
+}
+
+fn expected(s: &str) -> Vec<Option<Value<'static>>> {
+    s.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| Value::from_str_owned(l).ok())
+        .collect()
+}
+
+#[test]
+fn test_parse_ordered() {
+    let s = input();
+    let want = expected(&s);
+    assert_eq!(want.len(), 1980);
+    for &threads in &[1, 3, 0] {
+        for &chunk in &[1, 100, 4096, 1 << 20] {
+            let got = NdjsonParser::new(threads).chunk_size(chunk).parse(&s);
+            let got = got.into_iter().map(|r| r.ok()).collect::<Vec<_>>();
+            assert_eq!(got, want, "{} {}", threads, chunk);
+        }
+    }
+    assert!(NdjsonParser::new(4).parse("").is_empty());
+    assert_eq!(NdjsonParser::new(4).parse("1").len(), 1);
+    let cjson = NdjsonParser::new(2)
+        .dialect(Dialect::LOOSE)
+        .parse("1 // one\n/* two */ 2");
+    let cjson = cjson.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(cjson, vec![json!(1), json!(2)]);
+}
+
+#[test]
+fn test_for_each_unordered() {
+    let s = input();
+    let seen = Mutex::new(Vec::new());
+    NdjsonParser::new(4)
+        .chunk_size(512)
+        .for_each_unordered(&s, |off, r| {
+            let line = s[off..].lines().next().unwrap();
+            assert_eq!(r.is_ok(), Value::from_str(line).is_ok(), "{:?}", line);
+            seen.lock().unwrap().push(off);
+        });
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort_unstable();
+    assert_eq!(seen.len(), 1980);
+    seen.dedup();
+    assert_eq!(seen.len(), 1980);
+}
@@ -0,0 +1,108 @@
+use smoljson::{json, pointer, Reader, Value};
+
+const DOC: &str = r#"{
+    "meta": {"version": 3, "name": "dump"},
+    "big": [[1, 2, {"x": "A\n"}], {"nested": [true, false, null]}, -1.5e3],
+    "a/b": 1,
+    "m~n": 2,
+    "items": [{"id": "a"}, {"id": "b"}, {"id": "c"}]
+}"#;
+
+#[test]
+fn test_value_pointer() {
+    let v = Value::from_str(DOC).unwrap();
+    assert_eq!(v.pointer(""), Some(&v));
+    assert_eq!(v.pointer("/meta/version"), Some(&json!(3)));
+    assert_eq!(v.pointer("/items/2/id"), Some(&json!("c")));
+    assert_eq!(v.pointer("/a~1b"), Some(&json!(1)));
+    assert_eq!(v.pointer("/m~0n"), Some(&json!(2)));
+    assert_eq!(v.pointer("/items/02/id"), None);
+    assert_eq!(v.pointer("/items/-"), None);
+    assert_eq!(v.pointer("/items/5"), None);
+    assert_eq!(v.pointer("meta"), None);
+    assert_eq!(v.pointer("/m~2n"), None);
+}
+
+#[test]
+fn test_extract() {
+    let ptrs = &[
+        "/meta/version",
+        "/items/1/id",
+        "/big/0/2",
+        "/big/0/2/x",
+        "/nope",
+        "/meta/version/x",
+        "/a~1b",
+        "bad",
+    ];
+    let mut r = Reader::new(DOC);
+    let got = pointer::extract(&mut r, ptrs).unwrap();
+    let v = Value::from_str(DOC).unwrap();
+    for (p, g) in ptrs.iter().zip(&got) {
+        assert_eq!(g.as_ref(), v.pointer(p), "{:?}", p);
+    }
+    assert_eq!(
+        pointer::extract(&mut Reader::new(DOC), &[""]).unwrap(),
+        vec![Some(v)]
+    );
+}
+
+#[test]
+fn test_extract_stops_early() {
+    // Everything after the match is garbage, but we never look at it.
+    let mut r = Reader::new(r#"[{"skip": [1, {"a": "\"}"}]}, {"x": 1}, ???"#);
+    let got = pointer::extract(&mut r, &["/1/x"]).unwrap();
+    assert_eq!(got, vec![Some(json!(1))]);
+    // But the rest of an object has to be read, in case of duplicate keys.
+    let mut r = Reader::new(r#"[{"x": 1, ???}]"#);
+    assert!(pointer::extract(&mut r, &["/0/x"]).is_err());
+
+    let mut r = Reader::new(r#"{"skip": [1, {"a": }], "want": 1}"#);
+    assert!(pointer::extract(&mut r, &["/want"]).is_err());
+    let mut r = Reader::new(r#"{"skip": [1, 1e], "want": 1}"#);
+    assert!(pointer::extract(&mut r, &["/want"]).is_err());
+    let mut r = Reader::new(r#"{"skip": 01, "want": 1}"#);
+    assert!(pointer::extract(&mut r, &["/want"]).is_err());
+}
+
+#[test]
+fn test_extract_duplicate_keys() {
+    // Like `Value`, the last duplicate wins.
+    for src in &[
+        r#"{"a":1,"a":2}"#,
+        r#"{"a": {"x": 1}, "b": 2, "a": {"x": 3}}"#,
+        r#"{"a": {"x": 1}, "b": 2, "a": [5]}"#,
+        r#"[{"a": [{"x": 1}], "a": [{"x": 3, "x": 4}]}]"#,
+    ] {
+        let v = Value::from_str(src).unwrap();
+        let ptrs = &["/a", "/a/x", "/b", "/0/a/0/x", "/a/0"];
+        let got = pointer::extract(&mut Reader::new(src), ptrs).unwrap();
+        for (p, g) in ptrs.iter().zip(&got) {
+            assert_eq!(g.as_ref(), v.pointer(p), "{:?} in {:?}", p, src);
+        }
+    }
+}
+
+#[test]
+fn test_skip_value() {
+    let mut r = Reader::new(r#"[{"a": [1, "é\\", -2.5e+3, {}]}, true, null] 5"#);
+    r.skip_value().unwrap();
+    assert_eq!(r.read_u64().unwrap(), 5);
+    r.finish().unwrap();
+    for bad in &[
+        "[1,",
+        r#""\q""#,
+        "{1: 2}",
+        "tru",
+        "[}",
+        r#"{"a" 1}"#,
+        "-",
+        "[1-2]",
+        "1e",
+        "[.5]",
+        "01",
+        "[1.]",
+    ] {
+        assert!(Reader::new(bad).skip_value().is_err(), "{:?}", bad);
+    }
+}
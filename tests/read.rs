@@ -216,8 +216,8 @@ fn test_escape_surrogates2() {
 const DIALECTS: &[Dialect] = &[
     Dialect::STRICT,
     Dialect::STRICT.comments(true),
-    // Dialect::STRICT.trailing_comma(true),
-    // Dialect::STRICT.comments(true).trailing_comma(true),
+    Dialect::STRICT.trailing_comma(true),
+    Dialect::STRICT.comments(true).trailing_comma(true),
 ];
 
 #[track_caller]
@@ -348,3 +348,123 @@ true }"#,
     none("tr/**/ue");
     none("tr/*/ue");
 }
+
+#[test]
+fn test_array_items() {
+    let src = r#" [ {"id": 1, "tags": ["a"]}, {"id": 2}, 3, "four" ] "#;
+    let mut r = Reader::new(src);
+    let items = r.array_items().collect::<Result<Vec<_>, _>>().unwrap();
+    r.finish().unwrap();
+    assert_eq!(
+        items,
+        vec![
+            json!({"id": 1, "tags": ["a"]}),
+            json!({"id": 2}),
+            json!(3),
+            json!("four")
+        ]
+    );
+
+    let mut r = Reader::new("[]");
+    assert!(r.array_items().next().is_none());
+    r.finish().unwrap();
+
+    let mut r = Reader::new("[1, 2, 30]");
+    let nums = r
+        .array_items_with(Reader::read_u64)
+        .collect::<Result<Vec<_>, _>>();
+    assert_eq!(nums.unwrap(), vec![1, 2, 30]);
+
+    for bad in &["{}", "[1, 2", "[1 2]", "[1,]", "[,]"] {
+        let mut r = Reader::with_dialect(bad, Dialect::STRICT);
+        let mut it = r.array_items();
+        assert!(it.any(|v| v.is_err()), "{:?}", bad);
+        assert!(it.next().is_none(), "{:?}", bad);
+    }
+}
+
+#[test]
+fn test_from_str_owned() {
+    let owned = {
+        let src = String::from(TESTJSON);
+        Value::from_str_owned(&src).unwrap()
+    };
+    assert_eq!(owned, Value::from_str(TESTJSON).unwrap());
+    let escaped = r#"{"k\n": ["v\t", "plain"]}"#;
+    assert_eq!(
+        Value::from_str_owned(escaped).unwrap(),
+        json!({"k\n": ["v\t", "plain"]})
+    );
+    assert!(Value::from_str_owned("[1] 2").is_err());
+    assert!(Value::from_str_owned_with("[1 /**/]", Dialect::STRICT).is_err());
+    assert!(Value::from_str_owned_with("[1 /**/]", Dialect::LOOSE).is_ok());
+}
+
+#[test]
+fn test_strip_comments() {
+    let src = "{\n  // lead\n  \"a\": \"/* not */\", /* mul\r\n ti\u{e9} */ \"b\": [1,2] //\n}";
+    let spaced = strip_comments(src, true);
+    assert_eq!(spaced.len(), src.len());
+    assert_eq!(spaced.lines().count(), src.lines().count());
+    assert_eq!(spaced.find("\"b\""), src.find("\"b\""));
+    let strict = Value::from_str_with(&spaced, Dialect::STRICT).unwrap();
+    assert_eq!(strict, json!({"a": "/* not */", "b": [1, 2]}));
+    assert_eq!(
+        strip_comments(src, false),
+        "{\n   \n  \"a\": \"/* not */\",   \"b\": [1,2]  \n}"
+    );
+    // Comments still separate tokens.
+    assert_eq!(strip_comments("[1/**/2]", false), "[1 2]");
+    assert_eq!(strip_comments("[1/**/2]", true), "[1    2]");
+    assert!(Value::from_str(&strip_comments("[1/**/2]", false)).is_err());
+    // Broken input is passed through, with an unterminated comment removed.
+    assert_eq!(strip_comments("[1, \"x /* y", false), "[1, \"x /* y");
+    assert_eq!(strip_comments("[1 /* y", false), "[1  ");
+    assert_eq!(strip_comments("[1 /* y", true), "[1     ");
+}
+
+#[test]
+fn test_negative_zero() {
+    let v = Value::from_str("[-0, -0.0, -0e1]").unwrap();
+    assert_eq!(v, json!([0, 0, 0]));
+}
+
+#[test]
+fn test_trailing_comma() {
+    let d = Dialect::STRICT.trailing_comma(true);
+    let src = r#"{"a": [1, 2,], "b": {"c": null,},}"#;
+    let want = json!({"a": [1, 2], "b": {"c": null}});
+    assert_eq!(Value::from_str_with(src, d).unwrap(), want);
+    assert!(Value::from_str_with(src, Dialect::STRICT).is_err());
+    let mut r = Reader::with_dialect(src, d);
+    r.skip_value().unwrap();
+    r.finish().unwrap();
+    let mut r = Reader::with_dialect("[1, 2 ,]", d);
+    let items = r.array_items().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(items, [json!(1), json!(2)]);
+    for bad in &["[,]", "{,}", "[1,,]", r#"{"a": 1,,}"#, "[1,}"] {
+        assert!(Value::from_str_with(bad, d).is_err(), "{:?}", bad);
+        assert!(
+            Reader::with_dialect(bad, d).skip_value().is_err(),
+            "{:?}",
+            bad
+        );
+    }
+
+    let src = "{\n  \"a\": [1, 2,], // two\n  \"s\": \",]\",\n}";
+    let spaced = strip_comments_with(src, Dialect::LOOSE, true);
+    assert_eq!(spaced, "{\n  \"a\": [1, 2 ],       \n  \"s\": \",]\" \n}");
+    assert_eq!(
+        Value::from_str_with(&spaced, Dialect::STRICT).unwrap(),
+        json!({"a": [1, 2], "s": ",]"})
+    );
+    assert_eq!(
+        strip_comments_with(src, Dialect::LOOSE, false),
+        "{\n  \"a\": [1, 2],  \n  \"s\": \",]\"\n}"
+    );
+    // Without the flag, trailing commas are left alone.
+    assert_eq!(
+        strip_comments_with(src, Dialect::STRICT, false),
+        "{\n  \"a\": [1, 2,],  \n  \"s\": \",]\",\n}"
+    );
+}
@@ -0,0 +1,55 @@
+use smoljson::spanned::{Kind, SpannedValue};
+use smoljson::{Dialect, Value};
+
+const SRC: &str = r#"{
+    "server": {"port": 70000, "host": "lo\"cal"},
+    "list": [1, [true, null], {}],
+    "port": 1,
+    "port": 2
+}"#;
+
+#[track_caller]
+fn text(v: Option<&SpannedValue<'_>>) -> &'static str {
+    &SRC[v.unwrap().span.clone()]
+}
+
+#[test]
+fn test_spans() {
+    let v = SpannedValue::from_str(SRC).unwrap();
+    assert_eq!(v.span, 0..SRC.len());
+    assert_eq!(text(v.pointer("/server/port")), "70000");
+    assert_eq!(text(v.pointer("/server/host")), r#""lo\"cal""#);
+    assert_eq!(text(v.pointer("/list/1")), "[true, null]");
+    assert_eq!(text(v.pointer("/list/1/1")), "null");
+    assert_eq!(text(v.pointer("/list/2")), "{}");
+    assert_eq!(text(v.get("port")), "2");
+    assert!(v.pointer("/list/3").is_none());
+    assert!(v.pointer("/server/port/x").is_none());
+
+    let entry = v.get("server").unwrap().entry("host").unwrap();
+    assert_eq!(&SRC[entry.key_span.clone()], r#""host""#);
+    assert_eq!(entry.key, "host");
+    let keys = v
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|e| &*e.key)
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["server", "list", "port", "port"]);
+    assert!(v.at(0).is_none());
+    assert!(matches!(v.pointer("/list/0").unwrap().kind, Kind::Num(_)));
+
+    let plain = Value::from_str(SRC).unwrap();
+    assert_eq!(v.to_value(), plain);
+    assert_eq!(Value::from(v), plain);
+}
+
+#[test]
+fn test_spans_comments() {
+    let src = "/* a */ [ 1 /* b */, \"x\" ] // c";
+    assert!(SpannedValue::from_str_with(src, Dialect::STRICT).is_err());
+    let v = SpannedValue::from_str_with(src, Dialect::LOOSE).unwrap();
+    assert_eq!(&src[v.span.clone()], "[ 1 /* b */, \"x\" ]");
+    assert_eq!(v.pointer_span("/0"), Some(10..11));
+    assert_eq!(v.pointer_span("/1"), Some(21..24));
+}
@@ -0,0 +1,78 @@
+use smoljson::tape::Document;
+use smoljson::{json, Dialect, Value};
+
+const SRC: &str = r#"{
+    "name": "a\tb",
+    "plain": "xyz",
+    "n": [1, -2, 3.5, 1e300],
+    "empty": {"a": [], "o": {}},
+    "mixed": [null, true, false, {"k": [[]]}],
+    "dup": 1,
+    "dup": 2
+}"#;
+
+#[test]
+fn test_document() {
+    let doc = Document::from_str(SRC).unwrap();
+    let want = Value::from_str(SRC).unwrap();
+    assert_eq!(doc.to_value(), want);
+    let root = doc.root();
+    assert!(root.is_object());
+    assert_eq!(root.len(), Some(6 + 1));
+    assert_eq!(root.get("name").unwrap().as_str(), Some("a\tb"));
+    assert_eq!(root.get("plain").unwrap().as_str(), Some("xyz"));
+    assert_eq!(root.get("dup").unwrap().as_u64(), Some(2));
+    assert!(root.get("nope").is_none());
+    assert!(root.at(0).is_none());
+    let n = root.get("n").unwrap();
+    assert_eq!(n.len(), Some(4));
+    assert_eq!(n.at(1).unwrap().as_i64(), Some(-2));
+    assert_eq!(n.at(2).unwrap().as_f64(), Some(3.5));
+    assert!(n.at(4).is_none());
+    assert_eq!(
+        n.iter().map(|v| v.to_value()).collect::<Vec<_>>(),
+        want["n"].as_array().unwrap()
+    );
+    let mixed = root.get("mixed").unwrap();
+    assert!(mixed.at(0).unwrap().is_null());
+    assert_eq!(mixed.at(1).unwrap().as_bool(), Some(true));
+    let k = mixed.at(3).unwrap().get("k").unwrap();
+    assert_eq!(k.is_empty(), Some(false));
+    assert_eq!(k.at(0).unwrap().is_empty(), Some(true));
+    let keys = root.entries().map(|(k, _)| k).collect::<Vec<_>>();
+    assert_eq!(keys, ["name", "plain", "n", "empty", "mixed", "dup", "dup"]);
+
+    // Writing keeps the original key order.
+    let doc2 = Document::from_str(r#"{"k": [1, {"x": "\"y\""}], "e": []}"#).unwrap();
+    assert_eq!(doc2.to_string(false), r#"{"k":[1,{"x":"\"y\""}],"e":[]}"#);
+    let pretty = doc.to_string(true);
+    assert_eq!(Value::from_str(&pretty).unwrap(), want);
+
+    for scalar in &["1", "\"s\"", "null", " true "] {
+        let d = Document::from_str(scalar).unwrap();
+        assert_eq!(d.to_value(), Value::from_str(scalar).unwrap());
+    }
+}
+
+#[test]
+fn test_document_errors() {
+    for bad in &[
+        "",
+        "[1,]",
+        "{\"a\" 1}",
+        "{1: 2}",
+        "[1 2]",
+        "[",
+        "{}}",
+        "[] 1",
+    ] {
+        assert!(
+            Document::from_str_with(bad, Dialect::STRICT).is_err(),
+            "{:?}",
+            bad
+        );
+    }
+    assert!(Document::from_str_with("[1 /* c */]", Dialect::STRICT).is_err());
+    let d = Document::from_str_with("[1 /* c */]", Dialect::LOOSE).unwrap();
+    assert_eq!(d.to_value(), json!([1]));
+}
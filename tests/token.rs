@@ -0,0 +1,84 @@
+use smoljson::token::{TokenKind, Tokenizer};
+use smoljson::Dialect;
+
+fn tokens(src: &str, d: Dialect) -> Vec<(TokenKind, &str)> {
+    let toks = Tokenizer::new(src, d).collect::<Vec<_>>();
+    // Lossless: the spans cover the input, in order.
+    let mut pos = 0;
+    for (_, r) in &toks {
+        assert_eq!(r.start, pos);
+        assert!(r.end > r.start);
+        pos = r.end;
+    }
+    assert_eq!(pos, src.len());
+    toks.into_iter()
+        .filter(|(k, _)| *k != TokenKind::Whitespace)
+        .map(|(k, r)| (k, &src[r]))
+        .collect()
+}
+
+#[test]
+fn test_kinds() {
+    use TokenKind::*;
+    let src = "{\"a\": [1.5e3, \"s\", null, {\"k\": false}], /* c */ \"b\"\t:-0}";
+    assert_eq!(
+        tokens(src, Dialect::LOOSE),
+        [
+            (ObjectBegin, "{"),
+            (Key, "\"a\""),
+            (Colon, ":"),
+            (ArrayBegin, "["),
+            (Number, "1.5e3"),
+            (Comma, ","),
+            (String, "\"s\""),
+            (Comma, ","),
+            (Keyword, "null"),
+            (Comma, ","),
+            (ObjectBegin, "{"),
+            (Key, "\"k\""),
+            (Colon, ":"),
+            (Keyword, "false"),
+            (ObjectEnd, "}"),
+            (ArrayEnd, "]"),
+            (Comma, ","),
+            (BlockComment, "/* c */"),
+            (Key, "\"b\""),
+            (Colon, ":"),
+            (Number, "-0"),
+            (ObjectEnd, "}"),
+        ]
+    );
+    assert!(ObjectBegin.is_punctuation() && !Key.is_punctuation());
+    assert!(LineComment.is_trivia() && !Error.is_trivia());
+}
+
+#[test]
+fn test_errors() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("[01, 1., tru, @, \u{e9}, \"open", Dialect::STRICT),
+        [
+            (ArrayBegin, "["),
+            (Error, "01"),
+            (Comma, ","),
+            (Error, "1."),
+            (Comma, ","),
+            (Error, "tru"),
+            (Comma, ","),
+            (Error, "@"),
+            (Comma, ","),
+            (Error, "\u{e9}"),
+            (Comma, ","),
+            (Error, "\"open"),
+        ]
+    );
+    // Comments are errors unless the dialect allows them.
+    assert_eq!(
+        tokens("1 // x", Dialect::STRICT),
+        [(Number, "1"), (Error, "/"), (Error, "/"), (Error, "x")]
+    );
+    assert_eq!(
+        tokens("1 /* x", Dialect::LOOSE),
+        [(Number, "1"), (Error, "/* x")]
+    );
+}
@@ -0,0 +1,90 @@
+use smoljson::validate::{validate, ConstError};
+use smoljson::{Dialect, Value};
+
+const CONFIG: &str = smoljson::json_const!(r#"{"name": "x", "n": [1, -2.5e3, true, null]}"#);
+const CHECKED: Result<(), ConstError> = validate("[1,", Dialect::STRICT);
+
+fn check(src: &str, dialect: Dialect) -> Result<(), (usize, &'static str)> {
+    validate(src, dialect).map_err(|e| (e.offset, e.message))
+}
+
+#[test]
+fn test_valid() {
+    assert_eq!(Value::from_str(CONFIG).unwrap()["n"][0].as_u64(), Some(1));
+    assert_eq!(
+        CHECKED,
+        Err(ConstError {
+            offset: 3,
+            message: "expected a value"
+        })
+    );
+    for src in [
+        "0",
+        " -0.5E+10 ",
+        r#""\u00e9\n\"""#,
+        "[]",
+        "{}",
+        "[[], {}, [{}]]",
+        r#"{"a": {"b": [1, "two", false]}, "c": null}"#,
+        "\"😀\"",
+    ] {
+        assert_eq!(check(src, Dialect::STRICT), Ok(()), "{:?}", src);
+        assert!(Value::from_str(src).is_ok(), "{:?}", src);
+    }
+    let commented = "// top\n{\"a\": /* one */ 1 // end\n}";
+    assert_eq!(check(commented, Dialect::LOOSE), Ok(()));
+    assert_eq!(
+        check(commented, Dialect::STRICT),
+        Err((0, "expected a value"))
+    );
+    for src in ["[1,]", "{\"a\": [1, {},\n],\n}"] {
+        assert_eq!(check(src, Dialect::LOOSE), Ok(()), "{:?}", src);
+        assert!(
+            Value::from_str_with(src, Dialect::LOOSE).is_ok(),
+            "{:?}",
+            src
+        );
+    }
+    for src in ["[,]", "{,}", "[1,,]", "[1] ,"] {
+        assert!(check(src, Dialect::LOOSE).is_err(), "{:?}", src);
+        assert!(
+            Value::from_str_with(src, Dialect::LOOSE).is_err(),
+            "{:?}",
+            src
+        );
+    }
+}
+
+#[test]
+fn test_invalid() {
+    let cases: &[(&str, usize, &str)] = &[
+        ("", 0, "expected a value"),
+        ("[1,]", 3, "expected a value"),
+        ("{\"a\":1,}", 7, "expected a key"),
+        ("{\"a\" 1}", 5, "expected a colon"),
+        ("[1 2]", 3, "expected a comma"),
+        ("[1}", 2, "expected a comma"),
+        ("1 2", 2, "trailing characters"),
+        ("01", 1, "trailing characters"),
+        ("1.", 0, "invalid number"),
+        ("-", 0, "invalid number"),
+        ("tru", 0, "expected a value"),
+        ("\"abc", 0, "unterminated string"),
+        ("\"\\x\"", 1, "invalid escape"),
+        ("\"\\u12g4\"", 1, "invalid escape"),
+        ("\"a\tb\"", 2, "control character in string"),
+        ("[1, /* x", 4, "unterminated comment"),
+    ];
+    for &(src, offset, message) in cases {
+        let dialect = Dialect::LOOSE.trailing_comma(false);
+        assert_eq!(check(src, dialect), Err((offset, message)), "{:?}", src);
+    }
+    let deep =
+        "[".repeat(smoljson::validate::MAX_DEPTH) + &"]".repeat(smoljson::validate::MAX_DEPTH);
+    assert_eq!(check(&deep, Dialect::STRICT), Ok(()));
+    let deeper = format!("[{}]", deep);
+    assert_eq!(
+        check(&deeper, Dialect::STRICT),
+        Err((128, "nested too deeply"))
+    );
+}
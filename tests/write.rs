@@ -72,3 +72,232 @@ const TJSON0: &str = r#"{
         {"foo":3,"bar":9,"baz":["333"],"quux":{"frob":false}}
     ]
 }"#;
+
+#[test]
+fn test_transcode() {
+    use smoljson::{write::transcode, Dialect};
+    let src = r#"{"z": [1.50, -0, 1E400, 12345678901234567890123],
+        // comment
+        "a": {"k": "A\n", "k": null, "e": {}, "f": []}, "t": true}"#;
+    let min = transcode(src, Dialect::LOOSE, false).unwrap();
+    assert_eq!(
+        min,
+        r#"{"z":[1.50,-0,1E400,12345678901234567890123],"a":{"k":"A\n","k":null,"e":{},"f":[]},"t":true}"#
+    );
+    let pretty = transcode(&min, Dialect::STRICT, true).unwrap();
+    assert_eq!(
+        pretty,
+        "{\n    \"z\": [\n        1.50,\n        -0,\n        1E400,\n        12345678901234567890123\n    ],\n    \"a\": {\n        \"k\": \"A\\n\",\n        \"k\": null,\n        \"e\": {},\n        \"f\": []\n    },\n    \"t\": true\n}"
+    );
+    assert_eq!(transcode(&pretty, Dialect::STRICT, false).unwrap(), min);
+    assert_eq!(
+        transcode(" \"s\" ", Dialect::STRICT, true).unwrap(),
+        "\"s\""
+    );
+    // Comments are only skipped if the dialect allows them.
+    assert!(transcode(src, Dialect::STRICT, false).is_err());
+    assert!(transcode("[1, 2", Dialect::STRICT, false).is_err());
+    assert!(transcode("[1] 2", Dialect::STRICT, false).is_err());
+}
+
+#[test]
+fn test_options() {
+    use smoljson::{json, write::WriterOptions};
+    let opts = WriterOptions::PRETTY
+        .indent("  ")
+        .newline("\r\n")
+        .colon_space(false)
+        .empty_space(true)
+        .final_newline(true);
+    let mut w = Writer::with_options(opts);
+    {
+        let mut o = w.object();
+        o.put("a", &1);
+        o.begin_array("b").put(true).put(Null);
+        o.begin_object("c");
+        o.begin_array("d").compact().put(1).put(2);
+    }
+    assert_eq!(
+        w.finish(),
+        "{\r\n  \"a\":1,\r\n  \"b\":[\r\n    true,\r\n    null\r\n  ],\r\n  \"c\":{ },\r\n  \"d\":[1,2]\r\n}\r\n"
+    );
+    let v = json!({"a": [], "b": [{}]});
+    assert_eq!(v.to_string_with(WriterOptions::PRETTY), v.to_string(true));
+    assert_eq!(
+        v.to_string_with(WriterOptions::COMPACT.final_newline(true)),
+        "{\"a\":[],\"b\":[{}]}\n"
+    );
+    assert_eq!(
+        v.to_string_with(WriterOptions::PRETTY.indent("\t")),
+        "{\n\t\"a\": [],\n\t\"b\": [\n\t\t{}\n\t]\n}"
+    );
+}
+
+#[test]
+fn test_max_width() {
+    use smoljson::{json, write::WriterOptions};
+    let opts = WriterOptions::PRETTY.max_width(Some(30));
+    let v = json!({
+        "name": "x",
+        "pos": [1, 2, 3],
+        "tags": ["a\"b,\nc", "d"],
+        "long": [100000, 200000, 300000, 400000],
+        "nested": {"a": {"b": []}, "c": [[1], [2]]}
+    });
+    assert_eq!(
+        v.to_string_with(opts),
+        r#"{
+    "long": [
+        100000,
+        200000,
+        300000,
+        400000
+    ],
+    "name": "x",
+    "nested": {
+        "a": {"b": []},
+        "c": [[1], [2]]
+    },
+    "pos": [1, 2, 3],
+    "tags": ["a\"b,\nc", "d"]
+}"#
+    );
+    assert_eq!(
+        json!([1, {"a": 2}]).to_string_with(opts),
+        r#"[1, {"a": 2}]"#
+    );
+    // Width is counted from the start of the line, and includes the key.
+    let mut w = Writer::with_options(opts.max_width(Some(17)));
+    {
+        let mut o = w.object();
+        o.begin_array("abc").put(1).put(2);
+        o.begin_array("abcd").put(1).put(2);
+        o.begin_array("x").compact().put(1).put(2);
+    }
+    assert_eq!(
+        w.finish(),
+        "{\n    \"abc\": [1, 2],\n    \"abcd\": [\n        1,\n        2\n    ],\n    \"x\": [1,2]\n}"
+    );
+}
+
+#[test]
+fn test_key_order() {
+    use smoljson::{json, write::KeyOrder, write::WriterOptions};
+    let v = json!({"b": 1, "\u{ff61}": 2, "\u{1f600}": 3, "a": {"z": [], "y": null}});
+    let keys = |order: KeyOrder| v.to_string_with(WriterOptions::COMPACT.key_order(order));
+    assert_eq!(keys(KeyOrder::Unchanged), v.to_string(false));
+    assert_eq!(
+        keys(KeyOrder::Lexicographic),
+        "{\"a\":{\"y\":null,\"z\":[]},\"b\":1,\"\u{ff61}\":2,\"\u{1f600}\":3}"
+    );
+    // U+1F600 is a surrogate pair in UTF-16, which sorts before U+FF61.
+    assert_eq!(
+        keys(KeyOrder::Utf16),
+        "{\"a\":{\"y\":null,\"z\":[]},\"b\":1,\"\u{1f600}\":3,\"\u{ff61}\":2}"
+    );
+    assert_eq!(
+        keys(KeyOrder::Custom(|a, b| b.cmp(a))),
+        "{\"\u{1f600}\":3,\"\u{ff61}\":2,\"b\":1,\"a\":{\"z\":[],\"y\":null}}"
+    );
+
+    // Streaming writers keep call order, apart from the priority keys.
+    let order = KeyOrder::Priority(&["name", "version"], &KeyOrder::Unchanged);
+    let mut w = Writer::with_options(WriterOptions::PRETTY.key_order(order));
+    {
+        let mut o = w.object();
+        o.put("z", &1);
+        o.begin_array("deps").put("x");
+        o.put("version", &"1.0");
+        o.put("a", &2);
+        o.put("name", &"pkg");
+    }
+    assert_eq!(
+        w.finish(),
+        "{\n    \"name\": \"pkg\",\n    \"version\": \"1.0\",\n    \"z\": 1,\n    \"deps\": [\n        \"x\"\n    ],\n    \"a\": 2\n}"
+    );
+}
+
+#[test]
+fn test_escape() {
+    use smoljson::write::{Escape, WriterOptions};
+    use smoljson::Value;
+    let s = |v: &str, e: Escape| Value::from(v).to_string_with(WriterOptions::COMPACT.escape(e));
+    // Every control character round-trips (U+0018 and up used to get the
+    // wrong digits).
+    for c in (0u8..0x20).map(char::from) {
+        let text = c.to_string();
+        let out = s(&text, Escape::MINIMAL);
+        assert_eq!(
+            Value::from_str(&out).unwrap(),
+            Value::from(&*text),
+            "{}",
+            out
+        );
+    }
+    assert_eq!(s("\x18\x1f\x7f", Escape::MINIMAL), "\"\\u0018\\u001f\x7f\"");
+
+    let text = "<a href='/x'>&\u{2028}\u{2029}é😀</a>";
+    assert_eq!(s(text, Escape::MINIMAL), format!("\"{}\"", text));
+    assert_eq!(
+        s(text, Escape::JAVASCRIPT),
+        r#""\u003ca href=\u0027/x\u0027\u003e\u0026\u2028\u2029é😀\u003c/a\u003e""#
+    );
+    assert_eq!(
+        s(text, Escape::MINIMAL.ascii(true).slash(true)),
+        r#""<a href='\/x'>&\u2028\u2029\u00e9\ud83d\ude00<\/a>""#
+    );
+    for e in [Escape::JAVASCRIPT, Escape::MINIMAL.ascii(true).slash(true)] {
+        assert_eq!(Value::from_str(&s(text, e)).unwrap(), Value::from(text));
+    }
+    // Keys are escaped the same way.
+    let v = smoljson::json!({"<": 1});
+    let opts = WriterOptions::COMPACT.escape(Escape::JAVASCRIPT);
+    assert_eq!(v.to_string_with(opts), r#"{"\u003c":1}"#);
+}
+
+#[test]
+fn test_non_finite() {
+    use smoljson::write::{NonFinite, NonFiniteError, WriteJson, WriterOptions};
+    let write = |policy: NonFinite| {
+        let mut w = Writer::with_options(WriterOptions::COMPACT.non_finite(policy));
+        {
+            let mut a = w.array();
+            a.put(f64::NAN);
+            a.begin_object().put("x", &f64::INFINITY);
+        }
+        let mut top = Writer::with_options(WriterOptions::COMPACT.non_finite(policy));
+        f64::NEG_INFINITY.write_json(&mut top);
+        (w.try_finish(), top.try_finish())
+    };
+    let ok = |a: &str, b: &str| (Ok(a.to_string()), Ok(b.to_string()));
+    assert_eq!(write(NonFinite::Null), ok("[null,{\"x\":null}]", "null"));
+    assert_eq!(
+        write(NonFinite::String),
+        ok("[\"NaN\",{\"x\":\"Infinity\"}]", "\"-Infinity\"")
+    );
+    assert_eq!(
+        write(NonFinite::Literal),
+        ok("[NaN,{\"x\":Infinity}]", "-Infinity")
+    );
+    let max = f64::MAX.to_string();
+    assert_eq!(
+        write(NonFinite::Clamp),
+        ok(&format!("[null,{{\"x\":{}}}]", max), &format!("-{}", max))
+    );
+    let (a, b) = write(NonFinite::Error);
+    assert!(a.is_err() && b.is_err());
+    let strict = WriterOptions::COMPACT.non_finite(NonFinite::Error);
+    let v = smoljson::json!({"a": [1.5, null]});
+    assert_eq!(
+        v.try_to_string_with(strict).as_deref(),
+        Ok(r#"{"a":[1.5,null]}"#)
+    );
+    let v = smoljson::json!({"a": [1.5, (f64::NAN)]});
+    assert_eq!(v.try_to_string_with(strict), Err(NonFiniteError));
+    assert_eq!(
+        v.try_to_string_with(WriterOptions::COMPACT).as_deref(),
+        Ok(r#"{"a":[1.5,null]}"#)
+    );
+    // The default is `Clamp`.
+    assert_eq!(WriterOptions::PRETTY.non_finite, NonFinite::Clamp);
+}